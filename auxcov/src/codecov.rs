@@ -13,7 +13,7 @@ use dmasm::Instruction;
 
 use grcov::{output_cobertura, CovResult, FunctionMap, ResultTuple};
 use instruction_hooking::disassemble_env::DisassembleEnv;
-use instruction_hooking::InstructionHook;
+use instruction_hooking::{HookControl, InstructionHook};
 
 struct TrackerContext {
 	output_file_name: String,
@@ -218,7 +218,7 @@ impl Drop for Tracker {
 }
 
 impl InstructionHook for Tracker {
-	fn handle_instruction(&mut self, ctx: *mut raw_types::procs::ExecutionContext) {
+	fn handle_instruction(&mut self, ctx: *mut raw_types::procs::ExecutionContext) -> HookControl {
 		let ctx_ref;
 		let proc_instance_ref;
 		unsafe {
@@ -227,6 +227,7 @@ impl InstructionHook for Tracker {
 		}
 
 		self.process_dbg_line(ctx_ref, proc_instance_ref);
+		HookControl::Keep
 	}
 }
 
@@ -88,14 +88,49 @@ pub fn init(attr: TokenStream, item: TokenStream) -> TokenStream {
 	code.into()
 }
 
+/// The `runtime_handler` attribute registers a function to be called whenever a DM runtime occurs.
+///
+/// Handlers either take just the error message, `fn(&str)`, or the message and the
+/// [StackTrace](../auxtools/debug/struct.StackTrace.html) of where it happened, `fn(&str, &StackTrace)`.
 #[proc_macro_attribute]
 pub fn runtime_handler(_attr: TokenStream, item: TokenStream) -> TokenStream {
 	let func = syn::parse_macro_input!(item as syn::ItemFn);
 	let func_name = &func.sig.ident;
 
+	// Handlers that take a second argument want the stack trace too
+	let inventory_define = match func.sig.inputs.len() {
+		2 => quote! {
+			auxtools::inventory::submit!(
+				auxtools::RuntimeErrorStackHook(#func_name)
+			);
+		},
+		_ => quote! {
+			auxtools::inventory::submit!(
+				auxtools::RuntimeErrorHook(#func_name)
+			);
+		},
+	};
+
+	let code = quote! {
+		#func
+		#inventory_define
+	};
+
+	code.into()
+}
+
+/// The `runtime_filter` attribute registers a function that can rewrite a DM runtime's message
+/// before BYOND reports it, `fn(&str) -> RuntimeAction`.
+///
+/// See [RuntimeFilter](../auxtools/struct.RuntimeFilter.html) for how filters are ordered.
+#[proc_macro_attribute]
+pub fn runtime_filter(_attr: TokenStream, item: TokenStream) -> TokenStream {
+	let func = syn::parse_macro_input!(item as syn::ItemFn);
+	let func_name = &func.sig.ident;
+
 	let inventory_define = quote! {
 		auxtools::inventory::submit!(
-			auxtools::RuntimeErrorHook(#func_name)
+			auxtools::RuntimeFilter(#func_name)
 		);
 	};
 
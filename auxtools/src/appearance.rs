@@ -0,0 +1,66 @@
+use crate::*;
+
+impl Value {
+	/// Equivalent to DM's `atom.icon_state`. An empty string when it's null, as it is by default.
+	pub fn icon_state(&self) -> DMResult<String> {
+		let icon_state = self.get(byond_string!("icon_state"))?;
+		if icon_state == Value::null() {
+			return Ok(String::new());
+		}
+		icon_state.as_string()
+	}
+
+	pub fn set_icon_state(&self, icon_state: &str) -> DMResult<()> {
+		self.set(byond_string!("icon_state"), Value::from_string(icon_state)?)
+	}
+
+	/// Equivalent to DM's `atom.dir`, such as `NORTH` (1) or `SOUTHEAST` (6).
+	pub fn dir(&self) -> DMResult<u8> {
+		Ok(self.get_number(byond_string!("dir"))? as u8)
+	}
+
+	/// Sets `dir`, rejecting anything but the eight directions, like `NORTH` or `SOUTHWEST`.
+	pub fn set_dir(&self, dir: u8) -> DMResult<()> {
+		// NORTH, SOUTH, EAST, WEST, NORTHEAST, NORTHWEST, SOUTHEAST and SOUTHWEST
+		if ![1, 2, 4, 8, 5, 9, 6, 10].contains(&dir) {
+			return Err(runtime::Runtime::typed(
+				runtime::RuntimeKind::InvalidArgument,
+				format!("{} isn't a direction", dir),
+			));
+		}
+		self.set(byond_string!("dir"), dir as f32)
+	}
+
+	/// Reads a var from this atom's `appearance`, the snapshot of it BYOND draws, so it can be
+	/// compared with the atom's own vars or read from an appearance that isn't on an atom anymore.
+	pub fn get_appearance_var<S: Into<StringRef>>(&self, name: S) -> DMResult {
+		self.get(byond_string!("appearance"))?.get(name)
+	}
+
+	/// Loads the icon file at `path`, relative to the world's directory, like DM's `icon()`. The
+	/// result can be assigned to an atom's `icon`.
+	///
+	/// auxtools can't load resources itself, so the game has to define a proc for it:
+	/// ```dm
+	/// /proc/auxtools_new_icon(path)
+	///     if (!fexists(path))
+	///         return null
+	///     return icon(file(path))
+	/// ```
+	/// The icon is loaded with a [guarded call](crate::Proc::call_guarded), so that needs
+	/// `/proc/auxtools_call_guarded` as well. A file that doesn't exist or isn't an icon is an `Err`.
+	pub fn new_icon(path: &str) -> DMResult {
+		let new_icon = crate::helper_proc!("/proc/auxtools_new_icon")
+			.ok_or_else(|| runtime!("new_icon needs /proc/auxtools_new_icon to be defined"))?;
+
+		let icon = new_icon
+			.call_guarded(&[&Value::from_string(path)?])
+			.map_err(|e| runtime!("couldn't load icon {}: {}", path, e.message))?;
+
+		if icon == Value::null() {
+			return Err(runtime!("icon file {} doesn't exist", path));
+		}
+
+		Ok(icon)
+	}
+}
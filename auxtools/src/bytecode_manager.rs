@@ -104,3 +104,22 @@ pub fn set_bytecode(proc: &Proc, mut bytecode: Vec<u32>) {
 		raw_types::misc::set_bytecode((*proc.entry).bytecode, ptr, len);
 	}
 }
+
+// Puts back the bytecode the proc had before `set_bytecode` was first used on it. The replacement
+// stays allocated until shutdown, as the proc may still be running it.
+pub fn restore_bytecode(proc: &Proc) -> bool {
+	let state = unsafe {
+		let ptr = BYTECODE_ALLOCATIONS.get();
+		(*ptr).as_mut().unwrap()
+	};
+
+	match state.original.remove(&proc.id) {
+		Some((ptr, len)) => {
+			unsafe {
+				raw_types::misc::set_bytecode((*proc.entry).bytecode, ptr, len);
+			}
+			true
+		}
+		None => false,
+	}
+}
@@ -0,0 +1,96 @@
+//! Counts how many times each proc is called, cheaply enough to leave on all the time.
+//!
+//! Only available with the `call_counter` feature. Every call that reaches auxtools' proc call hook is
+//! counted once, hooked or not, including calls made from Rust.
+use crate::raw_types::procs::ProcId;
+use std::ptr;
+use std::sync::atomic::{AtomicPtr, AtomicU64, Ordering};
+
+// Proc IDs are dense, so counters live in fixed-size chunks that are only allocated once a proc in
+// them is called. That's enough room for a million procs.
+const CHUNK_SIZE: usize = 1024;
+const MAX_CHUNKS: usize = 1024;
+
+type Chunk = [AtomicU64; CHUNK_SIZE];
+
+#[allow(clippy::declare_interior_mutable_const)]
+const ZERO: AtomicU64 = AtomicU64::new(0);
+#[allow(clippy::declare_interior_mutable_const)]
+const NO_CHUNK: AtomicPtr<Chunk> = AtomicPtr::new(ptr::null_mut());
+static CHUNKS: [AtomicPtr<Chunk>; MAX_CHUNKS] = [NO_CHUNK; MAX_CHUNKS];
+
+fn chunk(index: usize) -> Option<&'static Chunk> {
+	let ptr = CHUNKS[index].load(Ordering::Acquire);
+	unsafe { ptr.as_ref() }
+}
+
+pub(crate) fn record(proc_id: ProcId) {
+	let id = proc_id.0 as usize;
+	let index = id / CHUNK_SIZE;
+	if index >= MAX_CHUNKS {
+		return;
+	}
+
+	let chunk = match chunk(index) {
+		Some(chunk) => chunk,
+		None => {
+			let new = Box::into_raw(Box::new([ZERO; CHUNK_SIZE]));
+			match CHUNKS[index].compare_exchange(
+				ptr::null_mut(),
+				new,
+				Ordering::AcqRel,
+				Ordering::Acquire,
+			) {
+				Ok(_) => unsafe { &*new },
+				// Somebody else got there first, so use theirs
+				Err(existing) => unsafe {
+					drop(Box::from_raw(new));
+					&*existing
+				},
+			}
+		}
+	};
+
+	chunk[id % CHUNK_SIZE].fetch_add(1, Ordering::Relaxed);
+}
+
+/// How many times the proc has been called since auxtools was initialized, or since [reset_counts].
+pub fn call_count(proc_id: ProcId) -> u64 {
+	let id = proc_id.0 as usize;
+	if id / CHUNK_SIZE >= MAX_CHUNKS {
+		return 0;
+	}
+
+	chunk(id / CHUNK_SIZE).map_or(0, |chunk| chunk[id % CHUNK_SIZE].load(Ordering::Relaxed))
+}
+
+/// Sets every proc's count back to 0.
+pub fn reset_counts() {
+	for index in 0..MAX_CHUNKS {
+		if let Some(chunk) = chunk(index) {
+			for count in chunk.iter() {
+				count.store(0, Ordering::Relaxed);
+			}
+		}
+	}
+}
+
+#[cfg(test)]
+mod tests {
+	use super::*;
+
+	#[test]
+	fn counts_calls() {
+		record(ProcId(5));
+		record(ProcId(5));
+		record(ProcId(CHUNK_SIZE as u32 + 1));
+
+		assert_eq!(call_count(ProcId(5)), 2);
+		assert_eq!(call_count(ProcId(CHUNK_SIZE as u32 + 1)), 1);
+		assert_eq!(call_count(ProcId(6)), 0);
+		assert_eq!(call_count(ProcId(u32::MAX)), 0);
+
+		reset_counts();
+		assert_eq!(call_count(ProcId(5)), 0);
+	}
+}
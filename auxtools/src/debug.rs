@@ -1,7 +1,10 @@
 use crate::raw_types::{funcs, procs};
+use crate::runtime;
 use crate::Proc;
 use crate::StringRef;
 use crate::Value;
+use std::fmt;
+use std::marker::PhantomData;
 
 pub struct StackFrame {
 	pub context: *mut procs::ExecutionContext,
@@ -85,6 +88,36 @@ impl StackFrame {
 	}
 }
 
+/// The active call stack at a single point in time, innermost frame first.
+///
+/// Handed to runtime handlers that take a second argument, e.g. `fn handler(error: &str, trace: &StackTrace)`.
+pub struct StackTrace {
+	pub frames: Vec<StackFrame>,
+}
+
+impl StackTrace {
+	pub fn capture() -> StackTrace {
+		StackTrace {
+			frames: unsafe {
+				CallStacks::from_context(*funcs::CURRENT_EXECUTION_CONTEXT, CallStackKind::Active)
+			},
+		}
+	}
+}
+
+impl fmt::Display for StackTrace {
+	fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+		for frame in &self.frames {
+			write!(f, "{}", frame.proc.path)?;
+			if let (Some(file_name), Some(line_number)) = (&frame.file_name, frame.line_number) {
+				write!(f, " ({}:{})", file_name, line_number)?;
+			}
+			writeln!(f)?;
+		}
+		Ok(())
+	}
+}
+
 enum CallStackKind {
 	Active,
 	Suspended,
@@ -139,3 +172,197 @@ impl CallStacks {
 		}
 	}
 }
+
+/// Runs `f` with the execution context of the DM code that's currently running, or `None` if there
+/// isn't any.
+///
+/// Inside a proc hook this is the context of whatever called the hooked proc, since BYOND hasn't
+/// made one for the hooked proc yet. That makes it possible to check which proc a call came from.
+/// The reference only lives as long as `f`, because the context stops being valid once the code
+/// running it moves on.
+///
+/// # Examples
+///
+/// ```ignore
+/// #[hook("/proc/admin_only")]
+/// fn admin_only() {
+///     let caller = debug::with_current_context(|ctx| ctx.and_then(|ctx| ctx.proc()));
+///     if caller.map_or(true, |proc| !proc.path.starts_with("/datum/admins/")) {
+///         return Err(runtime!("admin_only called from outside /datum/admins"));
+///     }
+///     Ok(Value::null())
+/// }
+/// ```
+pub fn with_current_context<R, F: FnOnce(Option<ExecutionContextRef<'_>>) -> R>(f: F) -> R {
+	// Also null until auxtools has found where BYOND keeps it
+	if unsafe { funcs::CURRENT_EXECUTION_CONTEXT.is_null() } {
+		return f(None);
+	}
+
+	let ptr = unsafe { *funcs::CURRENT_EXECUTION_CONTEXT };
+	if ptr.is_null() {
+		return f(None);
+	}
+
+	f(Some(unsafe { ExecutionContextRef::from_raw(ptr) }))
+}
+
+/// A view into a running proc's [ExecutionContext](procs::ExecutionContext), such as the one
+/// instruction hooks are given.
+///
+/// Locals and `.` are safe to change between instructions, because BYOND looks them up every time
+/// an instruction uses them. The bytecode offset can be moved with [set_bytecode_offset](Self::set_bytecode_offset).
+/// Everything else should be treated as read-only: the VM relies on the operand stack, bytecode
+/// pointer and parent context staying consistent with each other, and writing to them
+/// mid-execution will corrupt it.
+pub struct ExecutionContextRef<'a> {
+	ptr: *mut procs::ExecutionContext,
+	phantom: PhantomData<&'a mut procs::ExecutionContext>,
+}
+
+impl<'a> ExecutionContextRef<'a> {
+	/// # Safety
+	/// `ptr` must point to a live execution context that outlives the returned reference.
+	pub unsafe fn from_raw(ptr: *mut procs::ExecutionContext) -> Self {
+		Self {
+			ptr,
+			phantom: PhantomData,
+		}
+	}
+
+	pub fn as_ptr(&self) -> *mut procs::ExecutionContext {
+		self.ptr
+	}
+
+	pub fn proc_id(&self) -> procs::ProcId {
+		unsafe { (*(*self.ptr).proc_instance).proc }
+	}
+
+	pub fn proc(&self) -> Option<Proc> {
+		Proc::from_id(self.proc_id())
+	}
+
+	/// The DM source file this context is running, or `None` if the proc was compiled without debug info.
+	pub fn file_name(&self) -> Option<StringRef> {
+		unsafe {
+			let filename = (*self.ptr).filename;
+			if filename.valid() {
+				Some(StringRef::from_id(filename))
+			} else {
+				None
+			}
+		}
+	}
+
+	/// The DM source line this context is on, or `None` if the proc was compiled without debug info.
+	///
+	/// BYOND only updates this when it executes a line-number instruction, so it's the line of the
+	/// statement currently running rather than of the exact instruction.
+	pub fn line_number(&self) -> Option<u32> {
+		self.file_name()?;
+		unsafe { Some((*self.ptr).line) }
+	}
+
+	pub fn bytecode_offset(&self) -> u16 {
+		unsafe { (*self.ptr).bytecode_offset }
+	}
+
+	/// Moves execution to another offset in the proc's bytecode. Used from an instruction hook,
+	/// this makes BYOND run the instruction at `offset` instead of the one it was about to.
+	///
+	/// Offsets outside of the bytecode are rejected, but it's up to the caller to make sure `offset`
+	/// is the start of an instruction and that the operand stack is in a sensible state for it.
+	pub fn set_bytecode_offset(&mut self, offset: u16) -> Result<(), runtime::Runtime> {
+		let proc = self
+			.proc()
+			.ok_or_else(|| runtime!("execution context has no proc"))?;
+		let (bytecode, len) = unsafe { proc.bytecode_mut_ptr() };
+
+		// If the proc's bytecode was replaced while this context was running we can't know its length
+		if unsafe { (*self.ptr).bytecode } != bytecode {
+			return Err(runtime!(
+				"{} is running outdated bytecode, refusing to move its bytecode offset",
+				proc.path
+			));
+		}
+
+		if offset >= len {
+			return Err(runtime::Runtime::typed(
+				runtime::RuntimeKind::IndexOutOfBounds,
+				format!(
+					"bytecode offset {} out of range ({} has {} words of bytecode)",
+					offset, proc.path, len
+				),
+			));
+		}
+
+		unsafe {
+			(*self.ptr).bytecode_offset = offset;
+		}
+
+		Ok(())
+	}
+
+	pub fn locals_count(&self) -> u16 {
+		unsafe { (*self.ptr).locals_count }
+	}
+
+	/// Reads a local variable by its index, in the same order as [Proc::local_names].
+	pub fn local(&self, index: u16) -> Option<Value> {
+		if index >= self.locals_count() {
+			return None;
+		}
+
+		unsafe { Some(Value::from_raw(*(*self.ptr).locals.add(index as usize))) }
+	}
+
+	/// Overwrites a local variable, releasing the reference held by its old value.
+	pub fn set_local(&mut self, index: u16, value: &Value) -> Result<(), runtime::Runtime> {
+		if index >= self.locals_count() {
+			return Err(runtime::Runtime::typed(
+				runtime::RuntimeKind::IndexOutOfBounds,
+				format!(
+					"local index {} out of range (proc has {} locals)",
+					index,
+					self.locals_count()
+				),
+			));
+		}
+
+		unsafe {
+			let slot = (*self.ptr).locals.add(index as usize);
+			funcs::inc_ref_count(value.raw);
+			let old = std::mem::replace(&mut *slot, value.raw);
+			drop(Value::from_raw_owned(old));
+		}
+
+		Ok(())
+	}
+
+	pub fn stack_size(&self) -> u16 {
+		unsafe { (*self.ptr).stack_size }
+	}
+
+	/// Reads a value off the operand stack without popping it. A `depth` of 0 is the top of the stack.
+	pub fn stack_peek(&self, depth: u16) -> Option<Value> {
+		let size = self.stack_size();
+		if depth >= size {
+			return None;
+		}
+
+		let index = (size - 1 - depth) as usize;
+		unsafe { Some(Value::from_raw(*(*self.ptr).stack.add(index))) }
+	}
+
+	pub fn dot(&self) -> Value {
+		unsafe { Value::from_raw((*self.ptr).dot) }
+	}
+
+	pub fn set_dot(&mut self, value: &Value) {
+		unsafe {
+			funcs::inc_ref_count(value.raw);
+			let old = std::mem::replace(&mut (*self.ptr).dot, value.raw);
+			drop(Value::from_raw_owned(old));
+		}
+	}
+}
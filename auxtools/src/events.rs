@@ -0,0 +1,192 @@
+//! Hooks for things most mods want to know about, so they don't all have to find the right procs themselves.
+//!
+//! Apart from [hook_topic], these only observe the procs; the game's own implementation still runs as normal.
+use crate::*;
+use std::cell::RefCell;
+
+thread_local! {
+	static WORLD_TICK: RefCell<Vec<fn()>> = RefCell::new(Vec::new());
+	static CLIENT_NEW: RefCell<Vec<fn(&Value)>> = RefCell::new(Vec::new());
+	static TOPIC: RefCell<Vec<TopicHandler>> = RefCell::new(Vec::new());
+	static DEL: RefCell<Vec<fn(&Value)>> = RefCell::new(Vec::new());
+	static STAT: RefCell<Vec<fn(&Value, &mut StatBuilder)>> = RefCell::new(Vec::new());
+}
+
+/// Handles a `/world/Topic(T, Addr, Master, Keys)` call. Gets those four args, with null for any the
+/// call didn't pass.
+pub type TopicHandler = fn(&Value, &Value, &Value, &Value) -> DMResult<ProcHookResult>;
+
+/// Calls `callback` every server tick, after `/world/Tick()` returns.
+///
+/// `/world/Tick()` was added in BYOND 515, and like any other proc it can only be hooked if the game's
+/// code defines it. Otherwise this returns [HookFailure::ProcNotFound], so callers can fall back to
+/// hooking their own subsystem loop instead.
+pub fn hook_world_tick(callback: fn()) -> Result<(), HookFailure> {
+	register(&WORLD_TICK, callback, || {
+		hooks::hook_post("/world/proc/Tick", world_tick)
+	})
+}
+
+/// Calls `callback` with the new client once `/client/New()` returns.
+///
+/// Like [hook_world_tick], this fails with [HookFailure::ProcNotFound] if the game doesn't define `/client/New()`.
+pub fn hook_client_new(callback: fn(&Value)) -> Result<(), HookFailure> {
+	register(&CLIENT_NEW, callback, || {
+		hooks::hook_post("/client/proc/New", client_new)
+	})
+}
+
+/// Lets `handler` answer queries sent to the server with `world.Export()` or similar.
+///
+/// Returning [ProcHookResult::Return] sends that value back as the response. [ProcHookResult::Continue]
+/// passes the query on to the next handler, and eventually to the game's own `/world/Topic()`.
+/// Errors are reported like any other hook's, and the query gets a null response.
+///
+/// Fails with [HookFailure::ProcNotFound] if the game doesn't define `/world/Topic()`.
+pub fn hook_topic(handler: TopicHandler) -> Result<(), HookFailure> {
+	register(&TOPIC, handler, || {
+		hooks::hook_chained("/world/proc/Topic", topic)
+	})
+}
+
+/// Calls `callback` with each datum that's about to be deleted, just before `/datum/Del()` runs.
+///
+/// This hooks DM's `/datum/Del()` rather than BYOND's internal delete, so it needs the game to define
+/// that proc, and only sees deletions that reach it: subtypes overriding `Del()` must call `..()`.
+/// The datum is on its way out, so `callback` shouldn't keep a reference to it or pass it anywhere
+/// that might; use [Value::raw] to remember which datum it was.
+pub fn hook_del(callback: fn(&Value)) -> Result<(), HookFailure> {
+	register(&DEL, callback, || hooks::hook_pre("/datum/proc/Del", del))
+}
+
+/// Collects the lines a [hook_stat] callback wants to add to a stat panel.
+#[derive(Default)]
+pub struct StatBuilder {
+	panel: Option<String>,
+	lines: Vec<(Option<String>, String, Value)>,
+}
+
+impl StatBuilder {
+	/// Puts the lines added after this on the `name` stat panel, like DM's `statpanel()`. Until it's
+	/// called, lines go on whichever panel the game's own `Stat()` left selected.
+	pub fn panel<S: Into<String>>(&mut self, name: S) -> &mut Self {
+		self.panel = Some(name.into());
+		self
+	}
+
+	/// Adds a line, like DM's `stat(name, value)`.
+	pub fn line<S: Into<String>, V: Into<Value>>(&mut self, name: S, value: V) -> &mut Self {
+		self.lines
+			.push((self.panel.clone(), name.into(), value.into()));
+		self
+	}
+}
+
+/// Lets `callback` add lines to the stat panel of each mob being looked at, after `/mob/Stat()` returns.
+///
+/// BYOND calls `/mob/Stat()` for every client's mob each time it refreshes their stat panels, which
+/// is several times a second, so callbacks should only format state they already have.
+///
+/// DM's `stat()` can't be called from Rust, so the game has to define a proc to call it for us:
+/// ```dm
+/// /proc/auxtools_stat(panel, name, value)
+/// 	if (!panel || statpanel(panel))
+/// 		stat(name, value)
+/// ```
+/// Fails with [HookFailure::ProcNotFound] if that or `/mob/Stat()` isn't defined.
+pub fn hook_stat(callback: fn(&Value, &mut StatBuilder)) -> Result<(), HookFailure> {
+	if Proc::find("/proc/auxtools_stat").is_none() {
+		return Err(HookFailure::ProcNotFound);
+	}
+
+	register(&STAT, callback, || hooks::hook_post("/mob/proc/Stat", stat))
+}
+
+// The proc only gets hooked for the first callback; after that it's already dispatching to the list
+fn register<T: 'static>(
+	key: &'static std::thread::LocalKey<RefCell<Vec<T>>>,
+	callback: T,
+	install: fn() -> Result<(), HookFailure>,
+) -> Result<(), HookFailure> {
+	if key.with(|callbacks| callbacks.borrow().is_empty()) {
+		install()?;
+	}
+
+	key.with(|callbacks| callbacks.borrow_mut().push(callback));
+	Ok(())
+}
+
+fn world_tick(_src: &Value, _usr: &Value, _args: &[Value], _ret: &Value) {
+	// Copied out so a callback can register another one without a double borrow
+	let callbacks = WORLD_TICK.with(|callbacks| callbacks.borrow().clone());
+	for callback in callbacks {
+		callback();
+	}
+}
+
+fn client_new(src: &Value, _usr: &Value, _args: &[Value], _ret: &Value) {
+	let callbacks = CLIENT_NEW.with(|callbacks| callbacks.borrow().clone());
+	for callback in callbacks {
+		callback(src);
+	}
+}
+
+fn topic(_src: &Value, _usr: &Value, args: &[Value]) -> DMResult<ProcHookResult> {
+	let null = Value::null();
+	let arg = |i: usize| args.get(i).unwrap_or(&null);
+
+	let handlers = TOPIC.with(|handlers| handlers.borrow().clone());
+	for handler in handlers {
+		if let ProcHookResult::Return(response) = handler(arg(0), arg(1), arg(2), arg(3))? {
+			return Ok(ProcHookResult::Return(response));
+		}
+	}
+
+	Ok(ProcHookResult::Continue)
+}
+
+fn del(src: &Value, _usr: &Value, _args: &mut Vec<Value>) {
+	let callbacks = DEL.with(|callbacks| callbacks.borrow().clone());
+	for callback in callbacks {
+		callback(src);
+	}
+}
+
+fn stat(src: &Value, usr: &Value, _args: &[Value], _ret: &Value) {
+	let mut builder = StatBuilder::default();
+	let callbacks = STAT.with(|callbacks| callbacks.borrow().clone());
+	for callback in callbacks {
+		callback(src, &mut builder);
+	}
+
+	if builder.lines.is_empty() {
+		return;
+	}
+
+	let auxtools_stat = match Proc::find("/proc/auxtools_stat") {
+		Some(proc) => proc,
+		None => return,
+	};
+
+	for (panel, name, value) in builder.lines {
+		let panel = match panel {
+			Some(panel) => Value::from_string(panel),
+			None => Ok(Value::null()),
+		};
+
+		// A line that can't be shown isn't worth interrupting the rest of the panel for. usr is
+		// passed along as it's whose panel stat() writes to.
+		if let (Ok(panel), Ok(name)) = (panel, Value::from_string(name)) {
+			let _ = auxtools_stat.call_with(&Value::null(), usr, &[&panel, &name, &value]);
+		}
+	}
+}
+
+// The procs themselves are unhooked by hooks::clear_hooks
+pub(crate) fn shutdown() {
+	WORLD_TICK.with(|callbacks| callbacks.borrow_mut().clear());
+	CLIENT_NEW.with(|callbacks| callbacks.borrow_mut().clear());
+	TOPIC.with(|handlers| handlers.borrow_mut().clear());
+	DEL.with(|callbacks| callbacks.borrow_mut().clear());
+	STAT.with(|callbacks| callbacks.borrow_mut().clear());
+}
@@ -1,4 +1,5 @@
 #include <stdint.h>
+#include <chrono>
 #include "hooks.h"
 
 // The type of the func defined in Byond
@@ -15,12 +16,16 @@ extern "C" {
 	// The original function - set by rust after hooking
 	Runtime_Ptr runtime_original = nullptr;
 	CallProcById_Ptr call_proc_by_id_original = nullptr;
+
+	// Toggled by profiler.rs
+	bool profiler_enabled = false;
 }
 
 // If the top of this stack is true, we replace byond's runtime exceptions with our own
 std::stack<bool> runtime_contexts({false});
 
-extern "C" void on_runtime(const char* pError);
+// May point pRewritten at a new message.
+extern "C" void on_runtime(const char* pError, const char** pRewritten);
 
 extern "C" void runtime_hook(char* pError) {
 	const char* pErrorCorrected = (pError != nullptr) ? pError : "<null>";
@@ -33,7 +38,13 @@ extern "C" void runtime_hook(char* pError) {
 		return;
 	}
 
-	on_runtime(pErrorCorrected);
+	const char* pRewritten = nullptr;
+	on_runtime(pErrorCorrected, &pRewritten);
+
+	if (pRewritten != nullptr) {
+		return runtime_original(const_cast<char*>(pRewritten));
+	}
+
 	return runtime_original(pError);
 }
 
@@ -49,6 +60,24 @@ extern "C" uint8_t call_proc_by_id_hook(
 	uint32_t unk_1,
 	uint32_t unk_2);
 
+// Lets our hooks run the original proc themselves, with the frame BYOND called them with
+extern "C" void call_proc_by_id_passthrough(
+	Value* ret,
+	Value usr,
+	uint32_t proc_type,
+	uint32_t proc_id,
+	uint32_t unk_0,
+	Value src,
+	Value* args,
+	uint32_t args_count,
+	uint32_t unk_1,
+	uint32_t unk_2)
+{
+	*ret = call_proc_by_id_original(usr, proc_type, proc_id, unk_0, src, args, args_count, unk_1, unk_2);
+}
+
+extern "C" void on_proc_call_timed(uint32_t proc_id, uint64_t nanoseconds);
+
 // A little function to handle the odd calling convention on Linux and pass-through to our rust hook
 // Used on Windows too
 extern "C" Value LINUX_REGPARM3 call_proc_by_id_hook_trampoline(
@@ -67,6 +96,13 @@ extern "C" Value LINUX_REGPARM3 call_proc_by_id_hook_trampoline(
 	if (call_proc_by_id_hook(&ret, usr, proc_type, proc_id, unk_0, src, args, args_count, unk_1, unk_2)) {
 		clean(ret);
 		return ret;
+	} else if (profiler_enabled) {
+		// Only time the original call, so our own overhead doesn't show up in the results
+		auto start = std::chrono::steady_clock::now();
+		ret = call_proc_by_id_original(usr, proc_type, proc_id, unk_0, src, args, args_count, unk_1, unk_2);
+		auto elapsed = std::chrono::duration_cast<std::chrono::nanoseconds>(std::chrono::steady_clock::now() - start);
+		on_proc_call_timed(proc_id, elapsed.count());
+		return ret;
 	} else {
 		return call_proc_by_id_original(usr, proc_type, proc_id, unk_0, src, args, args_count, unk_1, unk_2);
 	}
@@ -1,12 +1,16 @@
 use super::proc::Proc;
 use super::raw_types;
 use super::value::Value;
-use crate::runtime::DMResult;
+use crate::debug::StackTrace;
+use crate::runtime::{DMResult, Runtime};
 use detour::RawDetour;
 use fxhash::FxHashMap;
 use std::ffi::c_void;
 use std::os::raw::c_char;
-use std::{cell::RefCell, ffi::CStr};
+use std::{
+	cell::{Cell, RefCell},
+	ffi::{CStr, CString},
+};
 
 #[doc(hidden)]
 pub struct CompileTimeHook {
@@ -20,12 +24,50 @@ inventory::collect!(CompileTimeHook);
 pub struct RuntimeErrorHook(pub fn(&str));
 inventory::collect!(RuntimeErrorHook);
 
+#[doc(hidden)]
+pub struct RuntimeErrorStackHook(pub fn(&str, &StackTrace));
+inventory::collect!(RuntimeErrorStackHook);
+
+/// What a [RuntimeFilter] wants done with a runtime.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub enum RuntimeAction {
+	/// Let BYOND report the runtime as usual.
+	Display,
+	/// Let BYOND report the runtime, but with this message instead.
+	Rewrite(String),
+}
+
+/// A runtime handler that decides what happens to the runtime, registered with `#[runtime_filter]`.
+///
+/// Filters run before BYOND reports the runtime, and each one sees the message as rewritten by the
+/// filters before it. The order filters run in between themselves is unspecified, so filters that
+/// rewrite the same runtimes shouldn't rely on running first.
+///
+/// Only the message can be changed. Every runtime still reaches BYOND, which aborts the proc that
+/// caused it just as it would without any filters. Ordinary runtime handlers run after every filter
+/// and see the final message.
+pub struct RuntimeFilter(pub fn(&str) -> RuntimeAction);
+inventory::collect!(RuntimeFilter);
+
 extern "C" {
 	static mut call_proc_by_id_original: *const c_void;
 
 	static mut runtime_original: *const c_void;
 	fn runtime_hook(error: *const c_char);
 
+	fn call_proc_by_id_passthrough(
+		ret: *mut raw_types::values::Value,
+		usr: raw_types::values::Value,
+		proc_type: u32,
+		proc_id: raw_types::procs::ProcId,
+		unk_0: u32,
+		src: raw_types::values::Value,
+		args: *mut raw_types::values::Value,
+		args_count: usize,
+		unk_1: u32,
+		unk_2: u32,
+	);
+
 	fn call_proc_by_id_hook_trampoline(
 		usr: raw_types::values::Value,
 		proc_type: u32,
@@ -39,9 +81,15 @@ extern "C" {
 	) -> raw_types::values::Value;
 }
 
+// Enough to cover the jump a detour writes over the start of a 32-bit function
+const PATCH_LEN: usize = 5;
+
 struct Detours {
 	pub runtime_detour: Option<RawDetour>,
 	pub call_proc_detour: Option<RawDetour>,
+	// What the start of each detoured function looked like just after we patched it
+	pub runtime_patch: [u8; PATCH_LEN],
+	pub call_proc_patch: [u8; PATCH_LEN],
 }
 
 impl Detours {
@@ -49,16 +97,25 @@ impl Detours {
 		Self {
 			runtime_detour: None,
 			call_proc_detour: None,
+			runtime_patch: [0; PATCH_LEN],
+			call_proc_patch: [0; PATCH_LEN],
 		}
 	}
 }
 
+unsafe fn read_patch(target: *const c_void) -> [u8; PATCH_LEN] {
+	let mut patch = [0; PATCH_LEN];
+	std::ptr::copy_nonoverlapping(target as *const u8, patch.as_mut_ptr(), PATCH_LEN);
+	patch
+}
+
 thread_local!(static DETOURS: RefCell<Detours> = RefCell::new(Detours::new()));
 
 pub enum HookFailure {
 	NotInitialized,
 	ProcNotFound,
 	AlreadyHooked,
+	NotHooked,
 	UnknownFailure,
 }
 
@@ -68,33 +125,75 @@ impl std::fmt::Debug for HookFailure {
 			Self::NotInitialized => write!(f, "Library not initialized"),
 			Self::ProcNotFound => write!(f, "Proc not found"),
 			Self::AlreadyHooked => write!(f, "Proc is already hooked"),
+			Self::NotHooked => write!(f, "Proc is not hooked"),
 			Self::UnknownFailure => write!(f, "Unknown failure"),
 		}
 	}
 }
 
-pub fn init() -> Result<(), String> {
+impl std::fmt::Display for HookFailure {
+	fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+		std::fmt::Debug::fmt(self, f)
+	}
+}
+
+impl std::error::Error for HookFailure {}
+
+/// A detour into BYOND that couldn't be installed.
+#[derive(Debug)]
+pub struct DetourFailure {
+	/// Which function we were detouring, e.g. `call_proc_by_id`.
+	pub detour: &'static str,
+	pub reason: String,
+}
+
+impl DetourFailure {
+	pub fn new<E: std::fmt::Display>(detour: &'static str, reason: E) -> Self {
+		Self {
+			detour,
+			reason: reason.to_string(),
+		}
+	}
+}
+
+impl std::fmt::Display for DetourFailure {
+	fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+		write!(f, "Couldn't detour {}: {}", self.detour, self.reason)
+	}
+}
+
+impl std::error::Error for DetourFailure {}
+
+pub fn init() -> Result<(), DetourFailure> {
 	unsafe {
 		let runtime_hook = RawDetour::new(
 			raw_types::funcs::runtime_byond as *const (),
 			runtime_hook as *const (),
 		)
-		.unwrap();
+		.map_err(|e| DetourFailure::new("runtime", e))?;
 
-		runtime_hook.enable().unwrap();
+		runtime_hook
+			.enable()
+			.map_err(|e| DetourFailure::new("runtime", e))?;
 		runtime_original = std::mem::transmute(runtime_hook.trampoline());
 
 		let call_hook = RawDetour::new(
 			raw_types::funcs::call_proc_by_id_byond as *const (),
 			call_proc_by_id_hook_trampoline as *const (),
 		)
-		.unwrap();
+		.map_err(|e| DetourFailure::new("call_proc_by_id", e))?;
 
-		call_hook.enable().unwrap();
+		if let Err(e) = call_hook.enable() {
+			// Don't leave half of our hooks installed
+			let _ = runtime_hook.disable();
+			return Err(DetourFailure::new("call_proc_by_id", e));
+		}
 		call_proc_by_id_original = std::mem::transmute(call_hook.trampoline());
 
 		DETOURS.with(|detours_cell| {
 			let mut detours = detours_cell.borrow_mut();
+			detours.runtime_patch = read_patch(raw_types::funcs::runtime_byond);
+			detours.call_proc_patch = read_patch(raw_types::funcs::call_proc_by_id_byond);
 			detours.runtime_detour = Some(runtime_hook);
 			detours.call_proc_detour = Some(call_hook);
 		});
@@ -102,6 +201,64 @@ pub fn init() -> Result<(), String> {
 	Ok(())
 }
 
+/// The result of [verify_hooks].
+#[derive(Debug, Default)]
+pub struct HookHealth {
+	/// Detours that have been disabled or overwritten since we installed them, e.g. `call_proc_by_id`.
+	pub broken: Vec<&'static str>,
+}
+
+impl HookHealth {
+	pub fn is_healthy(&self) -> bool {
+		self.broken.is_empty()
+	}
+}
+
+/// Checks that our detours into BYOND are still in place, and haven't been disabled or patched over by
+/// something else hooking the same functions. If they have, proc hooks or runtime handlers silently stop working.
+///
+/// This only compares a few bytes per detour, so it's cheap enough to call every tick.
+pub fn verify_hooks() -> HookHealth {
+	let mut health = HookHealth::default();
+
+	DETOURS.with(|detours_cell| {
+		let detours = detours_cell.borrow();
+		let checks = [
+			(
+				"runtime",
+				&detours.runtime_detour,
+				detours.runtime_patch,
+				unsafe { raw_types::funcs::runtime_byond },
+				unsafe { runtime_original },
+			),
+			(
+				"call_proc_by_id",
+				&detours.call_proc_detour,
+				detours.call_proc_patch,
+				unsafe { raw_types::funcs::call_proc_by_id_byond },
+				unsafe { call_proc_by_id_original },
+			),
+		];
+
+		for (name, detour, patch, target, original) in checks.iter() {
+			let intact = match detour {
+				Some(detour) => unsafe {
+					detour.is_enabled()
+						&& *original == detour.trampoline() as *const () as *const c_void
+						&& read_patch(*target) == *patch
+				},
+				None => false,
+			};
+
+			if !intact {
+				health.broken.push(*name);
+			}
+		}
+	});
+
+	health
+}
+
 pub fn shutdown() {
 	unsafe {
 		DETOURS.with(|detours_cell| {
@@ -114,51 +271,498 @@ pub fn shutdown() {
 	}
 }
 
+/// Replaces a proc. Gets its src, usr and the args exactly as they were passed, see [Proc::split_args].
 pub type ProcHook = fn(&Value, &Value, Vec<Value>) -> DMResult;
 
+/// Like [ProcHook], but the args are only borrowed. Hooks that just read their args should prefer this,
+/// as it skips building a `Vec` and touching every arg's reference count on each call.
+pub type BorrowedProcHook = fn(&Value, &Value, &[Value]) -> DMResult;
+
+/// A hook that can either produce the proc's return value or defer to the next hook in the chain.
+pub type ChainedProcHook = fn(&Value, &Value, &[Value]) -> DMResult<ProcHookResult>;
+
+/// What a [ChainedProcHook] wants to happen after it runs.
+pub enum ProcHookResult {
+	/// Stop here and return this value from the proc.
+	Return(Value),
+	/// Pass the call on to the next hook, or to the original proc if this was the last one.
+	Continue,
+}
+
+/// Runs before a proc and any of its other hooks, and may change the arguments they receive.
+pub type PreProcHook = fn(&Value, &Value, &mut Vec<Value>);
+
+/// Runs after a proc has returned, and sees its arguments and return value.
+pub type PostProcHook = fn(&Value, &Value, &[Value], &Value);
+
+/// Sees a proc's src, usr and return value after it returns. See [observe_return].
+pub type ReturnObserver = fn(&Value, &Value, &Value);
+
+#[derive(Clone, Copy)]
+enum HookType {
+	Replace(ProcHook),
+	Borrowed(BorrowedProcHook),
+	Chained(ChainedProcHook),
+	Pre(PreProcHook),
+	Post(PostProcHook),
+	Observe(ReturnObserver),
+}
+
+impl HookType {
+	fn address(&self) -> usize {
+		match self {
+			Self::Replace(func) => *func as usize,
+			Self::Borrowed(func) => *func as usize,
+			Self::Chained(func) => *func as usize,
+			Self::Pre(func) => *func as usize,
+			Self::Post(func) => *func as usize,
+			Self::Observe(func) => *func as usize,
+		}
+	}
+}
+
+thread_local! {
+	// Hooks are run in the order they were registered
+	static PROC_HOOKS: RefCell<FxHashMap<raw_types::procs::ProcId, (Vec<HookType>, String)>> = RefCell::new(FxHashMap::default());
+}
+
+/// Called for every proc call, hooked or not. The args are borrowed, so this is cheap to run.
+pub type ProcCallHook = fn(raw_types::procs::ProcId, &Value, &Value, &[Value]);
+
 thread_local! {
-	static PROC_HOOKS: RefCell<FxHashMap<raw_types::procs::ProcId, (ProcHook, String)>> = RefCell::new(FxHashMap::default());
+	static PROC_CALL_HOOKS: RefCell<Vec<ProcCallHook>> = RefCell::new(Vec::new());
+}
+
+thread_local! {
+	// Set by `Proc::call_original` so that the next call to the proc skips its hook
+	static BYPASS_HOOK: Cell<Option<raw_types::procs::ProcId>> = Cell::new(None);
 }
 
 fn hook_by_id(
 	id: raw_types::procs::ProcId,
-	hook: ProcHook,
+	hook: HookType,
 	hook_path: String,
 ) -> Result<(), HookFailure> {
 	PROC_HOOKS.with(|h| {
 		let mut map = h.borrow_mut();
-		if map.contains_key(&id) {
+		let (hooks, path) = map.entry(id).or_insert_with(|| (vec![], hook_path));
+		if hooks.iter().any(|h| h.address() == hook.address()) {
 			return Err(HookFailure::AlreadyHooked);
-		} else {
-			map.insert(id, (hook, hook_path));
-			Ok(())
 		}
+		hooks.push(hook);
+		aux_log!(debug, "Hooked {} ({} hooks)", path, hooks.len());
+		Ok(())
 	})
 }
 
+fn unhook_by_id(id: raw_types::procs::ProcId) -> Result<(), HookFailure> {
+	PROC_HOOKS.with(|h| match h.borrow_mut().remove(&id) {
+		Some(_) => Ok(()),
+		None => Err(HookFailure::NotHooked),
+	})
+}
+
+// Removes a single hook, leaving any others on the proc in place
+fn unhook_one(id: raw_types::procs::ProcId, address: usize) {
+	PROC_HOOKS.with(|h| {
+		let mut map = h.borrow_mut();
+		if let Some((hooks, _)) = map.get_mut(&id) {
+			hooks.retain(|hook| hook.address() != address);
+			if hooks.is_empty() {
+				map.remove(&id);
+			}
+		}
+	});
+}
+
 pub fn clear_hooks() {
 	PROC_HOOKS.with(|h| h.borrow_mut().clear());
+	PROC_CALL_HOOKS.with(|h| h.borrow_mut().clear());
+}
+
+/// Registers a function to be called before every proc call, e.g. for profiling.
+///
+/// It only observes the call, so any hook on the called proc still runs and returns as usual.
+pub fn hook_all(hook: ProcCallHook) {
+	PROC_CALL_HOOKS.with(|h| h.borrow_mut().push(hook));
 }
 
+// Finds the proc for a path like `/mob/proc/Login`
+fn resolve_proc<S: Into<String>>(name: S) -> Result<Proc, HookFailure> {
+	super::proc::get_proc(name).ok_or(HookFailure::ProcNotFound)
+}
+
+/// Hooks the proc at `name`, such as `/proc/foo` or `/mob/proc/Login`.
+///
+/// A type's proc is only found if that type defines or overrides it, otherwise hooking it fails with
+/// [HookFailure::ProcNotFound]. auxtools can't see BYOND's type tree, so it can't tell you which parent
+/// the proc is inherited from. Hooking it catches calls on subtypes which don't override it, and calls
+/// made with `..()` from subtypes that do.
+///
+/// Verbs live in the same table as procs, so they're hooked the same way and `/mob/verb/say` and
+/// `/mob/proc/say` name the same thing. BYOND parses a verb's input into its arguments before calling it,
+/// so the hook gets ordinary values. `usr` is the mob using the verb and `src` is what it was used on,
+/// which for verbs on a mob or client is usually the player themselves.
+///
+/// # Examples
+///
+/// From the tests, where `/datum/auxtest_typed/child` doesn't override `speak()`:
+/// ```ignore
+/// fn hooked_speak(_: &Value, _: &Value, _: Vec<Value>) -> DMResult {
+///     Value::from_string("hooked")
+/// }
+///
+/// // Catches child.speak() as well
+/// hook("/datum/auxtest_typed/proc/speak", hooked_speak).unwrap();
+///
+/// // The child type has no speak() of its own to hook
+/// assert!(hook("/datum/auxtest_typed/child/proc/speak", hooked_speak).is_err());
+/// ```
 pub fn hook<S: Into<String>>(name: S, hook: ProcHook) -> Result<(), HookFailure> {
-	match super::proc::get_proc(name) {
-		Some(p) => hook_by_id(p.id, hook, p.path.to_owned()),
-		None => Err(HookFailure::ProcNotFound),
+	resolve_proc(name)?.hook(hook)
+}
+
+/// Hooks a proc like [hook], with its args converted to the given types first.
+///
+/// Each arg is converted with [FromValue](crate::FromValue). If one doesn't convert, or the proc
+/// was called with more args than are listed, the hook returns an error naming the proc and arg
+/// instead of running. Args the caller left out are null, as they would be in DM, so wrap optional
+/// ones in an `Option`.
+///
+/// Evaluates to the `Result<(), HookFailure>` from hooking the proc.
+///
+/// # Examples
+///
+/// ```ignore
+/// proc_hook!("/proc/repeat_text", |_src, _usr, text: String, times: f32| {
+///     Ok(Value::from_string(text.repeat(times as usize))?)
+/// })
+/// .unwrap();
+/// ```
+#[macro_export]
+macro_rules! proc_hook {
+	($path:literal, |$src:ident, $usr:ident $(, $arg:ident : $ty:ty)* $(,)?| $body:block) => {{
+		fn proc_hook(
+			$src: &$crate::Value,
+			$usr: &$crate::Value,
+			args: ::std::vec::Vec<$crate::Value>,
+		) -> $crate::DMResult {
+			let expected = <[&str]>::len(&[$(stringify!($arg)),*]);
+			if args.len() > expected {
+				return Err($crate::Runtime::typed(
+					$crate::RuntimeKind::TypeMismatch,
+					format!(
+						"{} takes {} args but was given {}",
+						$path,
+						expected,
+						args.len()
+					),
+				));
+			}
+
+			let mut args = args.into_iter();
+			$(
+				let $arg: $ty = $crate::FromValue::from_value(
+					&args.next().unwrap_or_else($crate::Value::null),
+				)
+				.map_err(|e| {
+					$crate::Runtime::typed(
+						e.kind,
+						format!("{} arg `{}`: {}", $path, stringify!($arg), e.message),
+					)
+				})?;
+			)*
+
+			$body
+		}
+
+		$crate::hook($path, proc_hook)
+	}};
+}
+
+/// Like [hook], but for paths only known at runtime, such as ones read from a config file.
+///
+/// If the proc isn't where we last saw it, BYOND's proc table is re-read before giving up. This picks up
+/// procs that didn't exist at init, and procs whose IDs changed since.
+pub fn hook_dynamic(path: &str, handler: ProcHook) -> Result<(), HookFailure> {
+	let is_current = |p: &Proc| Proc::from_id(p.id).map_or(false, |current| current.path == p.path);
+
+	let proc = match super::proc::get_proc(path).filter(is_current) {
+		Some(p) => p,
+		None => {
+			super::proc::refresh_procs();
+			resolve_proc(path)?
+		}
+	};
+
+	proc.hook(handler)
+}
+
+/// Like [hook], but the hook borrows its args instead of taking ownership of them.
+pub fn hook_borrowed<S: Into<String>>(name: S, hook: BorrowedProcHook) -> Result<(), HookFailure> {
+	resolve_proc(name)?.hook_borrowed(hook)
+}
+
+/// Removes the hook it was returned for when dropped. See [hook_scoped].
+///
+/// Hooks are kept per-thread, so this isn't `Send`; it has to be dropped on BYOND's main thread.
+#[must_use = "the hook is removed as soon as the guard is dropped"]
+pub struct HookGuard {
+	proc_id: raw_types::procs::ProcId,
+	address: usize,
+	phantom: std::marker::PhantomData<*const ()>,
+}
+
+impl Drop for HookGuard {
+	fn drop(&mut self) {
+		unhook_one(self.proc_id, self.address);
 	}
 }
 
+/// Hooks several procs at once, or none of them: if any hook fails, the ones already added by this
+/// call are removed again. The error says which hook failed, by index.
+pub fn hook_batch(hooks: &[(&str, ProcHook)]) -> Result<(), (usize, HookFailure)> {
+	let mut installed = vec![];
+
+	for (index, (name, hook)) in hooks.iter().enumerate() {
+		let result = resolve_proc(*name).and_then(|proc| proc.hook(*hook).map(|_| proc.id));
+		match result {
+			Ok(id) => installed.push((id, *hook as usize)),
+			Err(failure) => {
+				for (id, address) in installed {
+					unhook_one(id, address);
+				}
+				return Err((index, failure));
+			}
+		}
+	}
+
+	Ok(())
+}
+
+/// Like [hook], but the hook is removed again once the returned guard is dropped, including when
+/// unwinding from a panic. Only this hook is removed, so other hooks on the proc are left alone.
+pub fn hook_scoped<S: Into<String>>(name: S, hook: ProcHook) -> Result<HookGuard, HookFailure> {
+	let proc = resolve_proc(name)?;
+	proc.hook(hook)?;
+	Ok(HookGuard {
+		proc_id: proc.id,
+		address: hook as usize,
+		phantom: std::marker::PhantomData,
+	})
+}
+
+/// Adds a [ChainedProcHook] to a proc, after any hooks it already has.
+pub fn hook_chained<S: Into<String>>(name: S, hook: ChainedProcHook) -> Result<(), HookFailure> {
+	resolve_proc(name)?.hook_chained(hook)
+}
+
+/// Adds a [PreProcHook] to a proc. See [Proc::hook_pre].
+pub fn hook_pre<S: Into<String>>(name: S, hook: PreProcHook) -> Result<(), HookFailure> {
+	resolve_proc(name)?.hook_pre(hook)
+}
+
+/// Adds a [PostProcHook] to a proc. See [Proc::hook_post].
+pub fn hook_post<S: Into<String>>(name: S, hook: PostProcHook) -> Result<(), HookFailure> {
+	resolve_proc(name)?.hook_post(hook)
+}
+
+/// Calls `observer` with the return value of every call to a proc, without changing what it does.
+///
+/// Observers run after every [PostProcHook] on the proc, in the order they were added. They see the
+/// value the caller is about to get, whether it came from the original proc or a replacement hook.
+/// If the proc sleeps, that's whatever `.` held when it went to sleep, as that's when BYOND hands
+/// control back to the caller.
+///
+/// Observing a proc that has no other hooks is cheap: the original proc is run straight from our
+/// hook with BYOND's own args, so all it costs on top of the observers themselves is a copy of the
+/// proc's hook list. On a proc with pre or post hooks, observers share their path, which takes a
+/// reference to every arg for the duration of the call.
+pub fn observe_return<S: Into<String>>(
+	name: S,
+	observer: ReturnObserver,
+) -> Result<(), HookFailure> {
+	let proc = resolve_proc(name)?;
+	hook_by_id(proc.id, HookType::Observe(observer), proc.path)
+}
+
+/// Returns true if the proc at `name` has any hooks. False if there's no such proc.
+///
+/// A proc being hooked doesn't stop other hooks being added to it. They run after the existing ones,
+/// so a replacement hook that's already there means later hooks never run.
+pub fn is_hooked<S: Into<String>>(name: S) -> bool {
+	super::proc::get_proc(name).map_or(false, |proc| proc.is_hooked())
+}
+
+/// Every proc that currently has hooks, in no particular order.
+pub fn hooked_procs() -> Vec<raw_types::procs::ProcId> {
+	PROC_HOOKS.with(|h| h.borrow().keys().copied().collect())
+}
+
+/// Removes every hook from a single proc, leaving hooks on other procs in place.
+pub fn unhook<S: Into<String>>(name: S) -> Result<(), HookFailure> {
+	unhook_by_id(resolve_proc(name)?.id)
+}
+
 impl Proc {
+	/// Adds a hook to this proc which always returns, so hooks registered after it never run.
 	pub fn hook(&self, func: ProcHook) -> Result<(), HookFailure> {
-		hook_by_id(self.id, func, self.path.to_owned())
+		hook_by_id(self.id, HookType::Replace(func), self.path.to_owned())
+	}
+
+	/// Same as [hook](Self::hook), for a [BorrowedProcHook].
+	pub fn hook_borrowed(&self, func: BorrowedProcHook) -> Result<(), HookFailure> {
+		hook_by_id(self.id, HookType::Borrowed(func), self.path.to_owned())
+	}
+
+	/// Adds a hook to this proc which may pass the call on by returning [ProcHookResult::Continue].
+	///
+	/// Hooks run in the order they were added. If every hook continues, the original proc is called.
+	pub fn hook_chained(&self, func: ChainedProcHook) -> Result<(), HookFailure> {
+		hook_by_id(self.id, HookType::Chained(func), self.path.to_owned())
+	}
+
+	/// Adds a hook that runs before this proc is called, without replacing it.
+	///
+	/// When a proc with pre or post hooks or return observers is called, things happen in this order:
+	/// 1. Every pre hook, in the order they were added. Changes they make to the args are seen by everything after.
+	/// 2. Replacement and chained hooks, exactly as if there were no pre or post hooks.
+	/// 3. The original proc, unless a hook in step 2 returned a value. It's run straight from the hook,
+	///    with the same src, usr and call details BYOND called it with, and the args as the pre hooks
+	///    left them.
+	/// 4. Every post hook, in the order they were added, with the value the proc returned.
+	/// 5. Every [observe_return] observer, in the order they were added.
+	///
+	/// If the original proc sleeps, BYOND returns to its caller as soon as it does. Steps 4 and 5
+	/// happen then, with whatever `.` held at that point rather than what the proc ends up returning.
+	pub fn hook_pre(&self, func: PreProcHook) -> Result<(), HookFailure> {
+		hook_by_id(self.id, HookType::Pre(func), self.path.to_owned())
+	}
+
+	/// Adds a hook that runs after this proc returns, without replacing it. See [hook_pre](Self::hook_pre)
+	/// for how this is ordered against other hooks.
+	pub fn hook_post(&self, func: PostProcHook) -> Result<(), HookFailure> {
+		hook_by_id(self.id, HookType::Post(func), self.path.to_owned())
+	}
+
+	/// See [is_hooked].
+	pub fn is_hooked(&self) -> bool {
+		PROC_HOOKS.with(|h| h.borrow().contains_key(&self.id))
+	}
+
+	/// Removes this proc's hooks, so calls to it go straight to the original implementation again.
+	pub fn unhook(&self) -> Result<(), HookFailure> {
+		unhook_by_id(self.id)
+	}
+
+	/// Calls the original implementation of this proc, skipping any hook installed on it.
+	///
+	/// This lets a hook wrap the proc it is attached to instead of reimplementing it.
+	/// Only the outermost call is affected, so any recursive calls the proc makes will still be hooked.
+	///
+	/// # Examples
+	///
+	/// Logs every call to `/proc/get_name` along with its result.
+	/// ```ignore
+	/// #[hook("/proc/get_name")]
+	/// fn get_name_hook(thing: Value) {
+	///     let original = Proc::find("/proc/get_name").unwrap();
+	///     let name = original.call_original(src, usr, &[&thing])?;
+	///     println!("get_name({}) = {}", thing, name);
+	///     Ok(name)
+	/// }
+	/// ```
+	pub fn call_original(&self, src: &Value, usr: &Value, args: &[&Value]) -> DMResult {
+		BYPASS_HOOK.with(|bypass| bypass.set(Some(self.id)));
+		let result = self.call_with(src, usr, args);
+		// Don't leave the bypass lying around if the call never reached our hook.
+		BYPASS_HOOK.with(|bypass| bypass.set(None));
+		result
 	}
 }
 
+thread_local! {
+	// Keeps the last rewritten runtime message alive until BYOND has reported it
+	static REWRITTEN_RUNTIME: RefCell<Option<CString>> = RefCell::new(None);
+}
+
+// If a filter rewrote the message, `rewritten` is pointed at the new one.
 #[no_mangle]
-extern "C" fn on_runtime(error: *const c_char) {
-	let str = unsafe { CStr::from_ptr(error) }.to_string_lossy();
+extern "C" fn on_runtime(error: *const c_char, rewritten: *mut *const c_char) {
+	let original = unsafe { CStr::from_ptr(error) }.to_string_lossy();
+	let mut message = None;
 
+	for filter in inventory::iter::<RuntimeFilter> {
+		match filter.0(message.as_deref().unwrap_or(&original)) {
+			RuntimeAction::Display => {}
+			RuntimeAction::Rewrite(new) => message = Some(new),
+		}
+	}
+
+	run_runtime_handlers(message.as_deref().unwrap_or(&original));
+
+	if let Some(message) = message {
+		// BYOND can't take interior nuls, so cut the message off at the first one
+		let message = CString::new(message).unwrap_or_else(|e| {
+			let end = e.nul_position();
+			CString::new(&e.into_vec()[..end]).unwrap()
+		});
+		REWRITTEN_RUNTIME.with(|buffer| unsafe {
+			*rewritten = buffer.borrow_mut().insert(message).as_ptr();
+		});
+	}
+}
+
+fn run_runtime_handlers(error: &str) {
 	for func in inventory::iter::<RuntimeErrorHook> {
-		func.0(&str);
+		func.0(error);
+	}
+
+	// Walking the stack isn't free, so only do it if someone wants it
+	let mut trace = None;
+	for func in inventory::iter::<RuntimeErrorStackHook> {
+		func.0(error, trace.get_or_insert_with(StackTrace::capture));
+	}
+}
+
+// Everything BYOND passed to call_proc_by_id besides the args, so hooks can run the original proc
+// exactly as it was called
+#[derive(Clone, Copy)]
+struct CallFrame {
+	usr: raw_types::values::Value,
+	proc_type: u32,
+	proc_id: raw_types::procs::ProcId,
+	unk_0: u32,
+	src: raw_types::values::Value,
+	unk_1: u32,
+	unk_2: u32,
+}
+
+impl CallFrame {
+	// Runs the original proc without going through our hooks again. Like any proc call, this
+	// consumes the args.
+	unsafe fn call_original(
+		&self,
+		args: *mut raw_types::values::Value,
+		num_args: usize,
+	) -> raw_types::values::Value {
+		let mut ret = raw_types::values::Value {
+			tag: raw_types::values::ValueTag::Null,
+			data: raw_types::values::ValueData { id: 0 },
+		};
+		call_proc_by_id_passthrough(
+			&mut ret,
+			self.usr,
+			self.proc_type,
+			self.proc_id,
+			self.unk_0,
+			self.src,
+			args,
+			num_args,
+			self.unk_1,
+			self.unk_2,
+		);
+		ret
 	}
 }
 
@@ -166,53 +770,157 @@ extern "C" fn on_runtime(error: *const c_char) {
 extern "C" fn call_proc_by_id_hook(
 	ret: *mut raw_types::values::Value,
 	usr_raw: raw_types::values::Value,
-	_proc_type: u32,
+	proc_type: u32,
 	proc_id: raw_types::procs::ProcId,
-	_unknown1: u32,
+	unknown1: u32,
 	src_raw: raw_types::values::Value,
 	args_ptr: *mut raw_types::values::Value,
 	num_args: usize,
-	_unknown2: u32,
-	_unknown3: u32,
+	unknown2: u32,
+	unknown3: u32,
 ) -> u8 {
-	match PROC_HOOKS.with(|h| match h.borrow().get(&proc_id) {
-		Some((hook, path)) => {
-			let (src, usr, args) = unsafe {
-				(
-					Value::from_raw(src_raw),
-					Value::from_raw(usr_raw),
-					// Taking ownership of args here
-					std::slice::from_raw_parts(args_ptr, num_args)
-						.iter()
-						.map(|v| Value::from_raw_owned(*v))
-						.collect(),
-				)
-			};
+	let frame = CallFrame {
+		usr: usr_raw,
+		proc_type,
+		proc_id,
+		unk_0: unknown1,
+		src: src_raw,
+		unk_1: unknown2,
+		unk_2: unknown3,
+	};
 
-			let result = hook(&src, &usr, args);
+	// Hooks catch their own panics, see `catch_hook_panic`. This only catches panics in auxtools
+	// itself, which still mustn't unwind into BYOND.
+	let taken_over = Cell::new(false);
+	let result = std::panic::catch_unwind(std::panic::AssertUnwindSafe(|| {
+		run_call_hooks(ret, &frame, args_ptr, num_args, &taken_over)
+	}));
 
-			match result {
-				Ok(r) => {
-					let result_raw = (&r).raw;
-					// Stealing our reference out of the Value
-					std::mem::forget(r);
-					Some(result_raw)
-				}
-				Err(e) => {
-					Proc::find("/proc/auxtools_stack_trace")
-						.unwrap()
-						.call(&[&Value::from_string(format!(
-							"{} HookPath: {}",
-							e.message.as_str(),
-							path.as_str()
-						))
-						.unwrap()])
-						.unwrap();
-					Some(Value::null().raw)
-				}
+	match result {
+		Ok(hooked) => hooked,
+		Err(panic) => {
+			let path = Proc::from_id(proc_id).map_or_else(|| "?".to_owned(), |p| p.path);
+			report_hook_error(
+				&crate::runtime!("hook panicked: {}", panic_message(&*panic)),
+				&path,
+			);
+
+			// If no hook has taken the call yet, BYOND can still run the proc as if nothing happened
+			if !taken_over.get() {
+				return 0;
+			}
+
+			unsafe {
+				*ret = Value::null().raw;
 			}
+			1
+		}
+	}
+}
+
+fn panic_message(panic: &(dyn std::any::Any + Send)) -> String {
+	panic
+		.downcast_ref::<&str>()
+		.map(|s| s.to_string())
+		.or_else(|| panic.downcast_ref::<String>().cloned())
+		.unwrap_or_else(|| "unknown panic".to_owned())
+}
+
+// Unwinding into BYOND is undefined behaviour, so a panicking hook is turned into an error and
+// reported like any other
+fn catch_hook_panic<T, F: FnOnce() -> DMResult<T>>(hook: F) -> DMResult<T> {
+	std::panic::catch_unwind(std::panic::AssertUnwindSafe(hook))
+		.unwrap_or_else(|panic| Err(crate::runtime!("hook panicked: {}", panic_message(&*panic))))
+}
+
+// For hooks that can't fail or change the call. A panic is reported and the call carries on.
+fn run_side_hook<F: FnOnce()>(path: &str, hook: F) {
+	if let Err(e) = catch_hook_panic(|| {
+		hook();
+		Ok(())
+	}) {
+		report_hook_error(&e, path);
+	}
+}
+
+fn run_call_hooks(
+	ret: *mut raw_types::values::Value,
+	frame: &CallFrame,
+	args_ptr: *mut raw_types::values::Value,
+	num_args: usize,
+	taken_over: &Cell<bool>,
+) -> u8 {
+	let proc_id = frame.proc_id;
+
+	// Let `Proc::call_original` through without touching any hooks at all. The call was already
+	// seen by the hooks when it first came in.
+	if BYPASS_HOOK.with(|bypass| bypass.get() == Some(proc_id)) {
+		BYPASS_HOOK.with(|bypass| bypass.set(None));
+		return 0;
+	}
+
+	// Counted after the bypass check, so `call_original` doesn't count the same call twice
+	#[cfg(feature = "call_counter")]
+	crate::call_counter::record(proc_id);
+
+	// Hooks are copied out before running them, so they can (un)hook procs and call hooked procs
+	// without finding the RefCells already borrowed
+	let call_hooks = PROC_CALL_HOOKS.with(|h| h.borrow().clone());
+	if !call_hooks.is_empty() {
+		// These are all still owned by BYOND, we only borrow them for the duration of the call
+		let src_usr_raw = [frame.src, frame.usr];
+		let (src_usr, args) = unsafe {
+			(
+				Value::from_raw_slice(&src_usr_raw),
+				Value::from_raw_slice(std::slice::from_raw_parts(args_ptr, num_args)),
+			)
+		};
+
+		for hook in call_hooks {
+			if let Err(e) = catch_hook_panic(|| {
+				hook(proc_id, &src_usr[0], &src_usr[1], args);
+				Ok(())
+			}) {
+				let path = Proc::from_id(proc_id).map_or_else(|| "?".to_owned(), |p| p.path);
+				report_hook_error(&e, &path);
+			}
+		}
+	}
+
+	let hooked = PROC_HOOKS.with(|h| h.borrow().get(&proc_id).cloned());
+	match hooked.and_then(|(hooks, path)| {
+		let _timer = crate::watchdog::watch_hook(&path);
+		let (src, usr, raw_args) = unsafe {
+			(
+				Value::from_raw(frame.src),
+				Value::from_raw(frame.usr),
+				std::slice::from_raw_parts(args_ptr, num_args),
+			)
+		};
+
+		if hooks
+			.iter()
+			.all(|hook| matches!(hook, HookType::Observe(_)))
+		{
+			taken_over.set(true);
+			return Some(run_observers(
+				frame, &hooks, &path, &src, &usr, args_ptr, num_args,
+			));
+		}
+
+		let has_phases = hooks.iter().any(|hook| {
+			matches!(
+				hook,
+				HookType::Pre(_) | HookType::Post(_) | HookType::Observe(_)
+			)
+		});
+
+		if has_phases {
+			taken_over.set(true);
+			Some(run_phased_hooks(frame, &hooks, &path, &src, &usr, raw_args))
+		} else {
+			run_hooks(&hooks, &path, &src, &usr, raw_args, taken_over)
 		}
-		None => None,
 	}) {
 		Some(result) => {
 			unsafe {
@@ -223,3 +931,205 @@ extern "C" fn call_proc_by_id_hook(
 		None => 0,
 	}
 }
+
+/// What happens when a proc hook returns an error. Either way, the hooked proc returns null.
+#[derive(Clone, Copy, PartialEq, Eq, Debug)]
+pub enum HookErrorPolicy {
+	/// Pass the error to `/proc/auxtools_stack_trace`, which should `CRASH()` with it so it shows up
+	/// as a DM runtime. Falls back to [RuntimeHandlers](Self::RuntimeHandlers) if the game doesn't define it.
+	StackTrace,
+	/// Pass the error straight to every `#[runtime_handler]`, without involving DM.
+	RuntimeHandlers,
+	/// Drop the error.
+	Ignore,
+}
+
+thread_local! {
+	static HOOK_ERROR_POLICY: Cell<HookErrorPolicy> = Cell::new(HookErrorPolicy::StackTrace);
+}
+
+/// Sets what happens to errors returned by proc hooks. The default is [HookErrorPolicy::StackTrace].
+pub fn set_hook_error_policy(policy: HookErrorPolicy) {
+	HOOK_ERROR_POLICY.with(|p| p.set(policy));
+}
+
+fn report_hook_error(error: &Runtime, path: &str) {
+	let message = format!("{} HookPath: {}", error.message.as_str(), path);
+	aux_log!(warn, "{}", message);
+
+	let policy = HOOK_ERROR_POLICY.with(|p| p.get());
+	if policy == HookErrorPolicy::Ignore {
+		return;
+	}
+
+	if policy == HookErrorPolicy::StackTrace {
+		if let Some(stack_trace) = Proc::find("/proc/auxtools_stack_trace") {
+			if let Ok(message) = Value::from_string(message.as_str()) {
+				let _ = stack_trace.call(&[&message]);
+				return;
+			}
+		}
+	}
+
+	run_runtime_handlers(&message);
+}
+
+// Runs replacement and chained hooks. Returns None if they all continued, and the original proc should run.
+fn run_hooks(
+	hooks: &[HookType],
+	path: &str,
+	src: &Value,
+	usr: &Value,
+	raw_args: &[raw_types::values::Value],
+	taken_over: &Cell<bool>,
+) -> Option<raw_types::values::Value> {
+	// Borrowed straight from BYOND, so the args are still intact for the original proc if every hook continues
+	let borrowed_args = unsafe { Value::from_raw_slice(raw_args) };
+
+	for hook in hooks {
+		let result = match hook {
+			HookType::Replace(hook) => {
+				// Taking ownership of args here
+				taken_over.set(true);
+				let args = raw_args
+					.iter()
+					.map(|v| unsafe { Value::from_raw_owned(*v) })
+					.collect();
+				catch_hook_panic(|| hook(src, usr, args)).map(ProcHookResult::Return)
+			}
+			HookType::Borrowed(hook) => {
+				let result = catch_hook_panic(|| hook(src, usr, borrowed_args));
+				// The original proc won't be consuming the args, so release them ourselves
+				taken_over.set(true);
+				for v in raw_args {
+					drop(unsafe { Value::from_raw_owned(*v) });
+				}
+				result.map(ProcHookResult::Return)
+			}
+			HookType::Chained(hook) => {
+				let result = catch_hook_panic(|| hook(src, usr, borrowed_args));
+				if !matches!(result, Ok(ProcHookResult::Continue)) {
+					// The original proc won't be consuming the args, so release them ourselves
+					taken_over.set(true);
+					for v in raw_args {
+						drop(unsafe { Value::from_raw_owned(*v) });
+					}
+				}
+				result
+			}
+			HookType::Pre(_) | HookType::Post(_) | HookType::Observe(_) => continue,
+		};
+
+		match result {
+			Ok(ProcHookResult::Continue) => continue,
+			Ok(ProcHookResult::Return(r)) => return Some(r.into_raw()),
+			Err(e) => {
+				report_hook_error(&e, path);
+				return Some(Value::null().raw);
+			}
+		}
+	}
+
+	None
+}
+
+// Runs pre hooks, then the rest of the hooks or the original proc, then post hooks.
+fn run_phased_hooks(
+	frame: &CallFrame,
+	hooks: &[HookType],
+	path: &str,
+	src: &Value,
+	usr: &Value,
+	raw_args: &[raw_types::values::Value],
+) -> raw_types::values::Value {
+	// We always produce the return value ourselves here, so the args are ours to keep
+	let mut args: Vec<Value> = raw_args
+		.iter()
+		.map(|v| unsafe { Value::from_raw_owned(*v) })
+		.collect();
+
+	for hook in hooks {
+		if let HookType::Pre(hook) = hook {
+			run_side_hook(path, || hook(src, usr, &mut args));
+		}
+	}
+
+	let mut result = None;
+	for hook in hooks {
+		let hook_result = match hook {
+			HookType::Replace(hook) => {
+				catch_hook_panic(|| hook(src, usr, args.clone())).map(ProcHookResult::Return)
+			}
+			HookType::Borrowed(hook) => {
+				catch_hook_panic(|| hook(src, usr, &args)).map(ProcHookResult::Return)
+			}
+			HookType::Chained(hook) => catch_hook_panic(|| hook(src, usr, &args)),
+			HookType::Pre(_) | HookType::Post(_) | HookType::Observe(_) => continue,
+		};
+
+		match hook_result {
+			Ok(ProcHookResult::Continue) => continue,
+			Ok(ProcHookResult::Return(r)) => {
+				result = Some(r);
+				break;
+			}
+			Err(e) => {
+				report_hook_error(&e, path);
+				result = Some(Value::null());
+				break;
+			}
+		}
+	}
+
+	let result = match result {
+		Some(r) => r,
+		None => unsafe {
+			// The original proc consumes its args, but post hooks still need to see ours
+			let mut original_args: Vec<_> = args
+				.iter()
+				.map(|v| {
+					raw_types::funcs::inc_ref_count(v.raw);
+					v.raw
+				})
+				.collect();
+			Value::from_raw_owned(
+				frame.call_original(original_args.as_mut_ptr(), original_args.len()),
+			)
+		},
+	};
+
+	for hook in hooks {
+		if let HookType::Post(hook) = hook {
+			run_side_hook(path, || hook(src, usr, &args, &result));
+		}
+	}
+
+	for hook in hooks {
+		if let HookType::Observe(observer) = hook {
+			run_side_hook(path, || observer(src, usr, &result));
+		}
+	}
+
+	result.into_raw()
+}
+
+// Observers don't look at the args, so BYOND's are handed straight to the original proc.
+fn run_observers(
+	frame: &CallFrame,
+	hooks: &[HookType],
+	path: &str,
+	src: &Value,
+	usr: &Value,
+	args_ptr: *mut raw_types::values::Value,
+	num_args: usize,
+) -> raw_types::values::Value {
+	let result = unsafe { Value::from_raw_owned(frame.call_original(args_ptr, num_args)) };
+
+	for hook in hooks {
+		if let HookType::Observe(observer) = hook {
+			run_side_hook(path, || observer(src, usr, &result));
+		}
+	}
+
+	result.into_raw()
+}
@@ -1,4 +1,6 @@
 use crate::inventory;
+use once_cell::sync::OnceCell;
+use std::thread::{self, ThreadId};
 
 #[derive(PartialEq, Clone, Copy)]
 pub enum InitLevel {
@@ -17,6 +19,41 @@ pub fn set_init_level(level: InitLevel) {
 	unsafe { REQUIRED_INIT = level }
 }
 
+// BYOND only ever calls into us from its main thread, so whichever thread runs init is it
+static MAIN_THREAD: OnceCell<ThreadId> = OnceCell::new();
+
+pub(crate) fn set_main_thread() {
+	let _ = MAIN_THREAD.set(thread::current().id());
+}
+
+/// Returns true if we're running on BYOND's main thread.
+///
+/// Values, procs and everything else that talks to BYOND can only be used from this thread.
+/// Always false before auxtools has been initialized.
+pub fn on_main_thread() -> bool {
+	MAIN_THREAD.get() == Some(&thread::current().id())
+}
+
+/// Returns true once auxtools has finished initializing: signatures are found, detours are installed and
+/// the proc table is loaded, so procs can be found and hooked.
+///
+/// `auxtools_init` always gets this far before running any `#[init(full)]` functions and then any
+/// `#[init(partial)]` functions, so it's already true inside them. The order functions of the same kind
+/// run in isn't specified.
+pub fn is_initialized() -> bool {
+	get_init_level() == InitLevel::None
+}
+
+/// Like [is_initialized], but returns [HookFailure::NotInitialized](crate::HookFailure::NotInitialized)
+/// so it can be used with `?`.
+pub fn require_initialized() -> Result<(), crate::HookFailure> {
+	if is_initialized() {
+		Ok(())
+	} else {
+		Err(crate::HookFailure::NotInitialized)
+	}
+}
+
 //
 // Hooks that run on intiailization
 //
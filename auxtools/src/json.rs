@@ -0,0 +1,78 @@
+//! Converting values to JSON, for exporting game state to external tools. Needs the `serde_json` feature.
+use crate::*;
+use serde_json::{Map, Number, Value as Json};
+
+impl Value {
+	/// Converts this value to JSON, descending at most `max_depth` levels into lists and datums.
+	///
+	/// - `null`, numbers and strings become their JSON equivalents. Numbers JSON can't hold, like infinity, become `null`.
+	/// - Lists become arrays, unless they're associative, in which case they become objects keyed by [to_display_string](Value::to_display_string).
+	/// - Datums become objects of their vars.
+	/// - Anything else, and anything past `max_depth`, becomes its display string. This is how reference cycles are cut off.
+	pub fn to_json(&self, max_depth: usize) -> DMResult<Json> {
+		match self.raw.tag {
+			raw_types::values::ValueTag::Null => return Ok(Json::Null),
+			raw_types::values::ValueTag::Number => {
+				return Ok(
+					Number::from_f64(self.as_number()? as f64).map_or(Json::Null, Json::Number)
+				)
+			}
+			raw_types::values::ValueTag::String => return Ok(Json::String(self.as_string()?)),
+			_ => {}
+		}
+
+		if max_depth == 0 {
+			return Ok(Json::String(self.to_display_string()));
+		}
+
+		if List::is_list(self) {
+			return list_to_json(&self.as_list()?, max_depth - 1);
+		}
+
+		match self.get_list(byond_string!("vars")) {
+			Ok(vars) => {
+				let mut object = Map::new();
+				for name in vars.iter() {
+					let value =
+						self.get(StringRef::from_value(name.clone()).ok_or_else(|| {
+							runtime!("{:?} has a var name that isn't a string", self)
+						})?)?;
+					object.insert(name.to_display_string(), value.to_json(max_depth - 1)?);
+				}
+				Ok(Json::Object(object))
+			}
+			Err(_) => Ok(Json::String(self.to_display_string())),
+		}
+	}
+}
+
+fn list_to_json(list: &List, max_depth: usize) -> DMResult<Json> {
+	// Only string keys are looked up, as getting a number from a list indexes it instead
+	let mut pairs = vec![];
+	let mut is_assoc = false;
+	for key in list.iter() {
+		let value = if key.raw.tag == raw_types::values::ValueTag::String {
+			list.get(key.clone())?
+		} else {
+			Value::null()
+		};
+
+		is_assoc |= value != Value::null();
+		pairs.push((key, value));
+	}
+
+	if !is_assoc {
+		return Ok(Json::Array(
+			pairs
+				.iter()
+				.map(|(key, _)| key.to_json(max_depth))
+				.collect::<DMResult<_>>()?,
+		));
+	}
+
+	let mut object = Map::new();
+	for (key, value) in pairs {
+		object.insert(key.to_display_string(), value.to_json(max_depth)?);
+	}
+	Ok(Json::Object(object))
+}
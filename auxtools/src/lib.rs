@@ -5,13 +5,38 @@
 //#[cfg(not(target_pointer_width = "32"))]
 //compile_error!("Auxtools must be compiled for a 32-bit target");
 
+// Forwards to the `log` crate when the feature is on, and compiles to nothing otherwise
+#[cfg(feature = "log")]
+macro_rules! aux_log {
+	($level:ident, $($arg:tt)*) => {
+		log::$level!(target: "auxtools", $($arg)*)
+	};
+}
+
+#[cfg(not(feature = "log"))]
+macro_rules! aux_log {
+	($level:ident, $($arg:tt)*) => {
+		if false {
+			let _ = format_args!($($arg)*);
+		}
+	};
+}
+
+mod appearance;
 mod byond_ffi;
 mod bytecode_manager;
+#[cfg(feature = "call_counter")]
+pub mod call_counter;
 pub mod debug;
+pub mod events;
 mod hooks;
 mod init;
+#[cfg(feature = "serde_json")]
+mod json;
 mod list;
+mod matrix;
 mod proc;
+pub mod profiler;
 pub mod raw_types;
 mod runtime;
 pub mod sigscan;
@@ -20,26 +45,44 @@ mod string_intern;
 mod value;
 mod value_from;
 pub mod version;
+mod watchdog;
 mod weak_value;
+pub mod world;
 
 use init::{get_init_level, set_init_level, InitLevel};
 
-pub use auxtools_impl::{full_shutdown, hook, init, pin_dll, runtime_handler, shutdown};
+pub use auxtools_impl::{
+	full_shutdown, hook, init, pin_dll, runtime_filter, runtime_handler, shutdown,
+};
 /// Used by the [pin_dll] macro to set dll pinning
 pub use ctor;
-pub use hooks::{CompileTimeHook, RuntimeErrorHook};
-pub use init::{FullInitFunc, FullShutdownFunc, PartialInitFunc, PartialShutdownFunc};
+pub use hooks::{
+	hook, hook_all, hook_batch, hook_borrowed, hook_chained, hook_dynamic, hook_post, hook_pre,
+	hook_scoped, hooked_procs, is_hooked, observe_return, set_hook_error_policy, unhook,
+	verify_hooks, BorrowedProcHook, ChainedProcHook, CompileTimeHook, DetourFailure,
+	HookErrorPolicy, HookFailure, HookGuard, HookHealth, PostProcHook, PreProcHook, ProcCallHook,
+	ProcHook, ProcHookResult, ReturnObserver, RuntimeAction, RuntimeErrorHook,
+	RuntimeErrorStackHook, RuntimeFilter,
+};
+pub use init::{
+	is_initialized, on_main_thread, require_initialized, FullInitFunc, FullShutdownFunc,
+	PartialInitFunc, PartialShutdownFunc,
+};
 /// Used by the [hook](attr.hook.html) macro to aggregate all compile-time hooks
 pub use inventory;
-pub use list::List;
-pub use proc::Proc;
+pub use list::{List, ListIter};
+pub use matrix::Matrix;
+pub use proc::{HelperProc, Proc};
 pub use raw_types::variables::VariableNameIdTable;
-pub use runtime::{DMResult, Runtime};
+pub use runtime::{DMResult, Runtime, RuntimeKind};
 use std::ffi::c_void;
 use std::sync::atomic::{AtomicBool, Ordering};
 pub use string::StringRef;
 pub use string_intern::InternedString;
 pub use value::Value;
+pub use value_from::FromValue;
+pub use version::{byond_version, require_version, ByondVersion};
+pub use watchdog::set_hook_watchdog;
 pub use weak_value::WeakValue;
 
 // We need winapi to call GetModuleHandleExW which lets us prevent our DLL from unloading.
@@ -186,6 +229,8 @@ fn pin_dll() -> Result<(), ()> {
 }
 
 byond_ffi_fn! { auxtools_init(_input) {
+	init::set_main_thread();
+
 	if get_init_level() == InitLevel::None {
 		return Some("SUCCESS".to_owned())
 	}
@@ -204,6 +249,11 @@ byond_ffi_fn! { auxtools_init(_input) {
 			return Some(format!("FAILED ({})", e));
 		}
 
+		// Lets dev servers that restart constantly skip most of the sigscanning
+		if let Some(path) = std::env::var_os("AUXTOOLS_SIGSCAN_CACHE") {
+			sigscan::enable_cache(path);
+		}
+
 		find_signatures! { byondcore,
 			(current_execution_context as *mut *mut raw_types::procs::ExecutionContext),
 			(suspended_procs as *mut raw_types::procs::SuspendedProcs),
@@ -256,9 +306,11 @@ byond_ffi_fn! { auxtools_init(_input) {
 			return Some("FAILED (Could not pin the library in memory.)".to_owned());
 		}
 
-		if let Err(_) = hooks::init() {
-			return Some("Failed (Couldn't initialize proc hooking)".to_owned());
+		if let Err(e) = hooks::init() {
+			aux_log!(error, "{}", e);
+			return Some(format!("FAILED ({})", e));
 		}
+		aux_log!(debug, "Installed runtime and call_proc_by_id detours");
 
 		set_init_level(InitLevel::Partial);
 	}
@@ -278,6 +330,7 @@ byond_ffi_fn! { auxtools_init(_input) {
 
 		for cthook in inventory::iter::<hooks::CompileTimeHook> {
 			if let Err(e) = hooks::hook(cthook.proc_path, cthook.hook) {
+				aux_log!(error, "Could not hook proc {}: {:?}", cthook.proc_path, e);
 				return Some(format!("FAILED (Could not hook proc {}: {:?})", cthook.proc_path, e));
 			}
 		}
@@ -302,6 +355,7 @@ byond_ffi_fn! { auxtools_init(_input) {
 		}
 	}
 
+	aux_log!(info, "Initialized for BYOND {}.{}", version::get().0, version::get().1);
 	Some("SUCCESS".to_owned())
 } }
 
@@ -314,6 +368,12 @@ byond_ffi_fn! { auxtools_shutdown(_input) {
 	bytecode_manager::shutdown();
 
 	hooks::clear_hooks();
+	events::shutdown();
+	watchdog::stop();
+	// Proc IDs mean something different after a reboot
+	#[cfg(feature = "call_counter")]
+	call_counter::reset_counts();
+	profiler::shutdown();
 	proc::clear_procs();
 
 	unsafe {
@@ -334,6 +394,11 @@ byond_ffi_fn! { auxtools_full_shutdown(_input) {
 		bytecode_manager::shutdown();
 
 		hooks::clear_hooks();
+		events::shutdown();
+		watchdog::stop();
+		#[cfg(feature = "call_counter")]
+		call_counter::reset_counts();
+		profiler::shutdown();
 		proc::clear_procs();
 
 		unsafe {
@@ -1,7 +1,16 @@
 use crate::*;
+use std::collections::HashMap;
 use std::iter::FromIterator;
 
 /// A wrapper around [Values](struct.Value.html) that make working with lists a little easier
+///
+/// # Indexing
+///
+/// Indices are **1-based**, exactly like in DM: `list.get(1)` is the first element and
+/// `list.get(list.len()?)` is the last. Passing a non-number to [get](Self::get) or [set](Self::set)
+/// does an associative lookup instead, like `list["key"]` in DM.
+///
+/// [iter](Self::iter) takes care of this for you and yields elements in list order.
 pub struct List {
 	value: Value,
 }
@@ -9,7 +18,10 @@ pub struct List {
 impl List {
 	pub fn from_value(val: &Value) -> DMResult<Self> {
 		if !Self::is_list(val) {
-			return Err(runtime!("attempted to create List from non-list value"));
+			return Err(Runtime::typed(
+				runtime::RuntimeKind::TypeMismatch,
+				"attempted to create List from non-list value",
+			));
 		}
 
 		Ok(Self { value: val.clone() })
@@ -36,6 +48,7 @@ impl List {
 		}
 	}
 
+	/// Equivalent to DM's `list[index]`. Numeric indices start at 1.
 	pub fn get<I: Into<Value>>(&self, index: I) -> runtime::DMResult {
 		let index = index.into();
 
@@ -56,6 +69,7 @@ impl List {
 		}
 	}
 
+	/// Equivalent to DM's `list[index] = value`. Numeric indices start at 1.
 	pub fn set<K: Into<Value>, V: Into<Value>>(
 		&self,
 		index: K,
@@ -75,34 +89,163 @@ impl List {
 		}
 	}
 
-	pub fn append<V: Into<Value>>(&self, value: V) {
+	/// Equivalent to DM's `list[key]` for an associative list. Unlike [get](Self::get), numbers are
+	/// never treated as indices, so passing one is an error: DM doesn't allow numbers as keys.
+	pub fn get_assoc<K: Into<Value>>(&self, key: K) -> DMResult {
+		let key = key.into();
+		if key.raw.tag == raw_types::values::ValueTag::Number {
+			return Err(runtime!("numbers can't be associative list keys"));
+		}
+
+		self.get(key)
+	}
+
+	/// Equivalent to DM's `list[key] = value` for an associative list. Like with
+	/// [get_assoc](Self::get_assoc), the key can't be a number.
+	pub fn set_assoc<K: Into<Value>, V: Into<Value>>(&self, key: K, value: V) -> DMResult<()> {
+		let key = key.into();
+		if key.raw.tag == raw_types::values::ValueTag::Number {
+			return Err(runtime!("numbers can't be associative list keys"));
+		}
+
+		self.set(key, value)
+	}
+
+	/// Adds `value` to the end of the list, like `list += value` in DM.
+	pub fn append<V: Into<Value>>(&self, value: V) -> DMResult<()> {
 		let value = value.into();
 
 		unsafe {
-			assert_eq!(
-				raw_types::funcs::append_to_list(self.value.raw, value.raw),
-				1
-			);
+			if raw_types::funcs::append_to_list(self.value.raw, value.raw) == 1 {
+				return Ok(());
+			}
 		}
+
+		Err(runtime!("failed to append to list"))
+	}
+
+	/// Same as [append](Self::append).
+	pub fn push<V: Into<Value>>(&self, value: V) -> DMResult<()> {
+		self.append(value)
 	}
 
-	pub fn remove<V: Into<Value>>(&self, value: V) {
+	/// Removes the element at `index` and returns it. Numeric indices start at 1, and the elements
+	/// after it move down to fill the gap. Any value associated with it is removed too.
+	///
+	/// This is done with DM's `list.Cut()`, so the game has to define a proc for it:
+	/// ```dm
+	/// /proc/auxtools_list_cut(list/L, start, end)
+	///     L.Cut(start, end)
+	/// ```
+	pub fn remove(&self, index: u32) -> DMResult {
+		let len = self.len()?;
+		if index == 0 || index > len {
+			return Err(runtime!(
+				"list index {} out of bounds for a list of length {}",
+				index,
+				len
+			));
+		}
+
+		let cut = crate::helper_proc!("/proc/auxtools_list_cut")
+			.ok_or_else(|| runtime!("remove needs /proc/auxtools_list_cut to be defined"))?;
+
+		let removed = self.get(index)?;
+		cut.call(&[&self.value, &Value::from(index), &Value::from(index + 1)])?;
+
+		Ok(removed)
+	}
+
+	/// Removes the last copy of `value` from the list, like `list -= value` in DM.
+	pub fn remove_value<V: Into<Value>>(&self, value: V) -> DMResult<()> {
 		let value = value.into();
 
 		unsafe {
-			assert_eq!(
-				raw_types::funcs::remove_from_list(self.value.raw, value.raw),
-				1
-			);
+			if raw_types::funcs::remove_from_list(self.value.raw, value.raw) == 1 {
+				return Ok(());
+			}
 		}
+
+		Err(runtime!("failed to remove from list"))
 	}
 
-	pub fn len(&self) -> u32 {
+	pub fn len(&self) -> DMResult<u32> {
 		let mut length: u32 = 0;
 		unsafe {
-			assert_eq!(raw_types::funcs::get_length(&mut length, self.value.raw), 1);
+			if raw_types::funcs::get_length(&mut length, self.value.raw) == 1 {
+				return Ok(length);
+			}
+		}
+
+		Err(runtime!("failed to get list length"))
+	}
+
+	/// Equivalent to DM's `value in list`. For associative lists this checks the keys.
+	pub fn contains(&self, value: &Value) -> bool {
+		self.iter().any(|v| v == *value)
+	}
+
+	/// Builds an associative list, like `list("a" = 1, "b" = 2)` in DM.
+	pub fn from_pairs<I: IntoIterator<Item = (Value, Value)>>(pairs: I) -> DMResult<Self> {
+		let res = Self::new();
+
+		for (key, value) in pairs {
+			res.set(key, value)?;
+		}
+
+		Ok(res)
+	}
+
+	/// Same as [from_pairs](Self::from_pairs), for a map that's already been built.
+	pub fn from_hashmap(map: HashMap<Value, Value>) -> DMResult<Self> {
+		Self::from_pairs(map)
+	}
+
+	/// Reads an associative list into a map of each key to its value.
+	///
+	/// DM lists can mix positional and associative entries. Positional entries become keys with a
+	/// null value, which is also what `list[key]` gives for them in DM. Numbers can't be associative
+	/// keys, so they always map to null. If the same key is in the list more than once, it's only in
+	/// the map once.
+	pub fn to_hashmap(&self) -> DMResult<HashMap<Value, Value>> {
+		let mut map = HashMap::with_capacity(self.len()? as usize);
+
+		for key in self.iter() {
+			// A number key would be read as an index instead
+			let value = match key.raw.tag {
+				raw_types::values::ValueTag::Number => Value::null(),
+				_ => self.get(&key)?,
+			};
+			map.insert(key, value);
+		}
+
+		Ok(map)
+	}
+
+	/// Like [to_hashmap](Self::to_hashmap), for the common case of a list keyed by strings. Fails if
+	/// any key isn't a string.
+	pub fn to_string_map(&self) -> DMResult<HashMap<String, Value>> {
+		self.to_hashmap()?
+			.into_iter()
+			.map(|(key, value)| Ok((key.as_string()?, value)))
+			.collect()
+	}
+
+	pub fn is_empty(&self) -> DMResult<bool> {
+		Ok(self.len()? == 0)
+	}
+
+	/// Iterates over the list's elements in order.
+	///
+	/// Elements are fetched one at a time as the iterator advances, so nothing is copied up front.
+	/// The length is read once, so don't resize the list while iterating. If it can't be read, the
+	/// iterator is empty.
+	pub fn iter(&self) -> ListIter<'_> {
+		ListIter {
+			list: self,
+			index: 1,
+			len: self.len().unwrap_or(0),
 		}
-		length
 	}
 
 	pub fn is_list(value: &Value) -> bool {
@@ -145,12 +288,51 @@ impl List {
 	}
 }
 
+/// Iterator over the elements of a [List], created by [List::iter].
+pub struct ListIter<'a> {
+	list: &'a List,
+	// BYOND lists are 1-indexed
+	index: u32,
+	len: u32,
+}
+
+impl<'a> Iterator for ListIter<'a> {
+	type Item = Value;
+
+	fn next(&mut self) -> Option<Value> {
+		if self.index > self.len {
+			return None;
+		}
+
+		let value = self.list.get(self.index).ok()?;
+		self.index += 1;
+		Some(value)
+	}
+
+	fn size_hint(&self) -> (usize, Option<usize>) {
+		let remaining = (self.len + 1 - self.index) as usize;
+		(remaining, Some(remaining))
+	}
+}
+
+impl<'a> IntoIterator for &'a List {
+	type Item = Value;
+	type IntoIter = ListIter<'a>;
+
+	fn into_iter(self) -> ListIter<'a> {
+		self.iter()
+	}
+}
+
 impl FromIterator<Value> for List {
+	/// Builds a list from the iterator's values, allocating it at the right size up front.
 	fn from_iter<I: IntoIterator<Item = Value>>(it: I) -> Self {
-		let res = Self::new();
+		let values: Vec<Value> = it.into_iter().collect();
+		let res = Self::with_size(values.len() as u32);
 
-		for val in it {
-			res.append(val);
+		for (i, val) in values.into_iter().enumerate() {
+			res.set(i as u32 + 1, val)
+				.expect("failed to set an element of a list made at the right size");
 		}
 
 		res
@@ -0,0 +1,75 @@
+use crate::*;
+
+/// The six components of a DM `/matrix`, reading left to right, top to bottom:
+///
+/// ```text
+/// a d 0
+/// b e 0
+/// c f 1
+/// ```
+///
+/// `c` and `f` are the x and y translation.
+#[derive(Clone, Copy, PartialEq, Debug)]
+pub struct Matrix {
+	pub a: f32,
+	pub b: f32,
+	pub c: f32,
+	pub d: f32,
+	pub e: f32,
+	pub f: f32,
+}
+
+impl Matrix {
+	pub const IDENTITY: Matrix = Matrix {
+		a: 1.0,
+		b: 0.0,
+		c: 0.0,
+		d: 0.0,
+		e: 1.0,
+		f: 0.0,
+	};
+
+	/// Writes these components into an existing `/matrix` datum.
+	pub fn write_to(&self, matrix: &Value) -> DMResult<()> {
+		check_is_matrix(matrix)?;
+		matrix.set(byond_string!("a"), self.a)?;
+		matrix.set(byond_string!("b"), self.b)?;
+		matrix.set(byond_string!("c"), self.c)?;
+		matrix.set(byond_string!("d"), self.d)?;
+		matrix.set(byond_string!("e"), self.e)?;
+		matrix.set(byond_string!("f"), self.f)?;
+		Ok(())
+	}
+}
+
+impl Default for Matrix {
+	fn default() -> Self {
+		Self::IDENTITY
+	}
+}
+
+fn check_is_matrix(value: &Value) -> DMResult<()> {
+	if value.is_exact_type("/matrix") {
+		return Ok(());
+	}
+
+	Err(runtime::Runtime::typed(
+		runtime::RuntimeKind::TypeMismatch,
+		format!("expected /matrix, got {:?}", value),
+	))
+}
+
+impl Value {
+	/// Reads the components of a `/matrix` datum, such as an atom's `transform`.
+	pub fn as_matrix(&self) -> DMResult<Matrix> {
+		check_is_matrix(self)?;
+		Ok(Matrix {
+			a: self.get_number(byond_string!("a"))?,
+			b: self.get_number(byond_string!("b"))?,
+			c: self.get_number(byond_string!("c"))?,
+			d: self.get_number(byond_string!("d"))?,
+			e: self.get_number(byond_string!("e"))?,
+			f: self.get_number(byond_string!("f"))?,
+		})
+	}
+}
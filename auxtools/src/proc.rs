@@ -1,9 +1,12 @@
 use crate::*;
 use ahash::RandomState;
 use fxhash::FxHashMap;
-use std::cell::RefCell;
+use std::cell::{RefCell, UnsafeCell};
 use std::collections::{hash_map::Entry, HashMap};
 use std::fmt;
+use std::fs::File;
+use std::io::{self, Write};
+use std::path::Path;
 
 //
 // ### A note on Override IDs
@@ -66,6 +69,22 @@ impl Proc {
 		})
 	}
 
+	/// Returns every proc the world has loaded, in proc ID order.
+	///
+	/// This is a snapshot that walks BYOND's proc array from the start, so it stays valid even if
+	/// the array grows later - the new procs just won't be in it.
+	pub fn all() -> Vec<Self> {
+		let mut procs = vec![];
+		let mut i: u32 = 0;
+
+		while let Some(proc) = Proc::from_id(raw_types::procs::ProcId(i)) {
+			procs.push(proc);
+			i += 1;
+		}
+
+		procs
+	}
+
 	pub unsafe fn file_name(&self) -> Option<StringRef> {
 		let bytecode = self.bytecode();
 		if bytecode.len() < 2 || bytecode[0] != 0x84 {
@@ -80,6 +99,11 @@ impl Proc {
 		Some(StringRef::from_id(file_id))
 	}
 
+	/// The names of this proc's declared parameters, in order. Pair with [call_named](Self::call_named)
+	/// to call procs by parameter name.
+	///
+	/// Default values aren't included because BYOND doesn't store them with the parameters; they're
+	/// compiled into the start of the proc's bytecode instead.
 	pub fn parameter_names(&self) -> Vec<StringRef> {
 		unsafe {
 			let (data, count) = raw_types::misc::get_parameters((*self.entry).parameters);
@@ -89,6 +113,16 @@ impl Proc {
 		}
 	}
 
+	/// Splits the args a hook received into those for this proc's declared parameters, and any extra
+	/// ones after them that DM code can only reach through `args`.
+	///
+	/// Hooks get exactly the args the caller passed. Omitted parameters are missing from the end rather
+	/// than filled in with their defaults, so the first half may be shorter than the parameter list.
+	pub fn split_args<'a>(&self, args: &'a [Value]) -> (&'a [Value], &'a [Value]) {
+		let declared = self.parameter_names().len().min(args.len());
+		args.split_at(declared)
+	}
+
 	pub fn local_names(&self) -> Vec<StringRef> {
 		unsafe {
 			let (names, count) = raw_types::misc::get_locals((*self.entry).locals);
@@ -98,10 +132,21 @@ impl Proc {
 		}
 	}
 
+	/// Replaces the bytecode BYOND runs for this proc. The original is put back when auxtools shuts
+	/// down, or earlier with [restore_bytecode](Self::restore_bytecode).
+	///
+	/// Nothing checks that `bytecode` is valid, and BYOND will happily run garbage until it crashes,
+	/// so only pass in bytecode that came from a disassembler or from [copy_bytecode](Self::copy_bytecode).
 	pub fn set_bytecode(&self, bytecode: Vec<u32>) {
 		crate::bytecode_manager::set_bytecode(self, bytecode);
 	}
 
+	/// Undoes [set_bytecode](Self::set_bytecode), giving the proc back the bytecode it was compiled
+	/// with. Returns false if its bytecode was never replaced.
+	pub fn restore_bytecode(&self) -> bool {
+		crate::bytecode_manager::restore_bytecode(self)
+	}
+
 	pub unsafe fn bytecode_mut_ptr(&self) -> (*mut u32, u16) {
 		raw_types::misc::get_bytecode((*self.entry).bytecode)
 	}
@@ -111,6 +156,50 @@ impl Proc {
 		std::slice::from_raw_parts(ptr, count as usize)
 	}
 
+	/// Copies this proc's bytecode out, e.g. for disassembling it later.
+	///
+	/// BYOND stores bytecode as 32-bit words. Returns `None` if the proc has no bytecode at all.
+	pub fn copy_bytecode(&self) -> Option<Vec<u32>> {
+		let (ptr, count) = unsafe { self.bytecode_mut_ptr() };
+		if ptr.is_null() || count == 0 {
+			return None;
+		}
+
+		Some(unsafe { std::slice::from_raw_parts(ptr, count as usize) }.to_vec())
+	}
+
+	/// Writes every proc's ID, path and bytecode to `path`, for tools that analyse bytecode offline.
+	///
+	/// The file is little-endian throughout:
+	///
+	/// - the magic bytes `AUXPROCS`, then a `u32` format version (currently 1) and a `u32` proc count
+	/// - for each proc, in ID order: its `u32` ID, a `u32` byte length and that many bytes of UTF-8
+	///   path, then a `u32` word count and that many `u32` words of bytecode
+	///
+	/// Procs without any bytecode are written with a word count of 0.
+	pub fn dump_all(path: &Path) -> io::Result<()> {
+		let procs = Self::all();
+		let mut out = io::BufWriter::new(File::create(path)?);
+
+		out.write_all(b"AUXPROCS")?;
+		out.write_all(&1u32.to_le_bytes())?;
+		out.write_all(&(procs.len() as u32).to_le_bytes())?;
+
+		for proc in procs {
+			let bytecode = proc.copy_bytecode().unwrap_or_default();
+
+			out.write_all(&proc.id.0.to_le_bytes())?;
+			out.write_all(&(proc.path.len() as u32).to_le_bytes())?;
+			out.write_all(proc.path.as_bytes())?;
+			out.write_all(&(bytecode.len() as u32).to_le_bytes())?;
+			for word in bytecode {
+				out.write_all(&word.to_le_bytes())?;
+			}
+		}
+
+		out.flush()
+	}
+
 	/// Calls a global proc with the given arguments.
 	///
 	/// # Examples
@@ -124,6 +213,126 @@ impl Proc {
 	/// }
 	/// ```
 	pub fn call(&self, args: &[&Value]) -> runtime::DMResult {
+		self.call_with(&Value::null(), &Value::null(), args)
+	}
+
+	/// Calls a global proc like [Proc::call], but a runtime inside the call comes back as an `Err`
+	/// holding its message instead of being reported.
+	///
+	/// The call goes through `/proc/auxtools_call_guarded`, which your DM code has to define:
+	/// ```dm
+	/// /proc/auxtools_call_guarded(proc_path, list/arguments)
+	///     try
+	///         return list(call(text2path(proc_path))(arglist(arguments)))
+	///     catch(var/exception/e)
+	///         return e.name
+	/// ```
+	/// The runtime is caught by DM's own `try`/`catch`, so BYOND unwinds the failed proc exactly as
+	/// it would for a runtime caught in DM and carrying on afterwards is safe. Guarded calls can be
+	/// nested, with a runtime caught by the innermost one it happens in.
+	///
+	/// Because DM calls the proc by path, it's always the last override of it that runs.
+	///
+	/// # Examples
+	///
+	/// ```ignore
+	/// let proc = Proc::find("/proc/might_runtime").unwrap();
+	/// if let Err(e) = proc.call_guarded(&[]) {
+	///     println!("might_runtime failed: {}", e.message);
+	/// }
+	/// ```
+	pub fn call_guarded(&self, args: &[&Value]) -> runtime::DMResult {
+		let helper = helper_proc!("/proc/auxtools_call_guarded").ok_or_else(|| {
+			runtime!("call_guarded needs /proc/auxtools_call_guarded to be defined")
+		})?;
+
+		let path = Value::from(unsafe { StringRef::from_id((*self.entry).path) });
+		let arguments = List::new();
+		for arg in args {
+			arguments.append(*arg)?;
+		}
+
+		let result = helper.call(&[&path, &Value::from(arguments)])?;
+		match List::from_value(&result) {
+			Ok(returned) => returned.get(1),
+			Err(_) => Err(Runtime::new(result.as_string()?)),
+		}
+	}
+
+	/// Calls a global proc with the given arguments without waiting for it, like `spawn()` in DM.
+	///
+	/// The call goes through `/proc/auxtools_spawn`, which your DM code has to define:
+	/// ```dm
+	/// /proc/auxtools_spawn(proc_path, list/arguments)
+	///     spawn(0)
+	///         call(text2path(proc_path))(arglist(arguments))
+	/// ```
+	/// BYOND runs the proc once whatever DM is running right now finishes or sleeps, so it never
+	/// re-enters the code that spawned it. Its return value is discarded, and any runtime in it is
+	/// reported as usual. Like [call_guarded](Self::call_guarded), it's always the last override of the
+	/// proc that runs.
+	pub fn spawn(&self, args: &[&Value]) -> DMResult<()> {
+		let helper = helper_proc!("/proc/auxtools_spawn")
+			.ok_or_else(|| runtime!("spawn needs /proc/auxtools_spawn to be defined"))?;
+
+		let path = Value::from(unsafe { StringRef::from_id((*self.entry).path) });
+		let arguments = List::new();
+		for arg in args {
+			arguments.append(*arg)?;
+		}
+
+		helper.call(&[&path, &Value::from(arguments)])?;
+		Ok(())
+	}
+
+	/// Calls a global proc with positional arguments followed by named ones, like `do_explode(3, power = 5)` in DM.
+	///
+	/// Named arguments are matched against the proc's parameter names and slotted into place, with any
+	/// parameters in between left as null. DM gives null arguments their default values, so skipped
+	/// parameters behave just as they do when called from DM.
+	///
+	/// # Examples
+	///
+	/// This function is equivalent to `return do_explode(3, power = 5)` in DM.
+	/// ```ignore
+	/// #[hook("/proc/my_proc")]
+	/// fn my_proc_hook() -> DMResult {
+	///     let proc = Proc::find("/proc/do_explode").unwrap();
+	///     proc.call_named(&[&Value::from(3.0)], &[("power", &Value::from(5.0))])
+	/// }
+	/// ```
+	pub fn call_named(&self, positional: &[&Value], named: &[(&str, &Value)]) -> runtime::DMResult {
+		let null = Value::null();
+		let mut args: Vec<&Value> = positional.to_vec();
+
+		if !named.is_empty() {
+			let param_names = self.parameter_names();
+
+			for (name, value) in named {
+				let index = param_names
+					.iter()
+					.position(|param| String::from(param) == *name)
+					.ok_or_else(|| runtime!("{} has no parameter named {}", self.path, name))?;
+
+				if index < positional.len() {
+					return Err(runtime!(
+						"{} was given parameter {} both positionally and by name",
+						self.path,
+						name
+					));
+				}
+
+				if args.len() <= index {
+					args.resize(index + 1, &null);
+				}
+				args[index] = value;
+			}
+		}
+
+		self.call(&args)
+	}
+
+	pub(crate) fn call_with(&self, src: &Value, usr: &Value, args: &[&Value]) -> runtime::DMResult {
 		let mut ret = raw_types::values::Value {
 			tag: raw_types::values::ValueTag::Null,
 			data: raw_types::values::ValueData { id: 0 },
@@ -139,11 +348,11 @@ impl Proc {
 
 			if raw_types::funcs::call_proc_by_id(
 				&mut ret,
-				Value::null().raw,
+				usr.raw,
 				0,
 				self.id,
 				0,
-				Value::null().raw,
+				src.raw,
 				args.as_ptr(),
 				args.len(),
 				0,
@@ -175,19 +384,34 @@ impl fmt::Debug for Proc {
 thread_local!(static PROCS_BY_NAME: RefCell<HashMap<String, Vec<Proc>, RandomState>> = RefCell::new(HashMap::with_hasher(RandomState::default())));
 thread_local!(static PROC_OVERRIDE_IDS: RefCell<FxHashMap<raw_types::procs::ProcId, u32>> = RefCell::new(FxHashMap::default()));
 
+/// Looks up a proc auxtools calls into, like `/proc/auxtools_call_guarded`, caching the result until
+/// the proc tables are next rebuilt. Evaluates to `None` if the world doesn't define it.
+#[macro_export]
+macro_rules! helper_proc {
+	($path:literal) => {
+		unsafe {
+			static mut STORE: $crate::HelperProc =
+				$crate::HelperProc($path, std::cell::UnsafeCell::new(None));
+			$crate::inventory::submit!(unsafe { &STORE });
+			(*STORE.1.get()).as_ref()
+		}
+	};
+}
+
+//hack
+unsafe impl Sync for HelperProc {}
+
+#[doc(hidden)]
+pub struct HelperProc(pub &'static str, pub UnsafeCell<Option<Proc>>);
+
+inventory::collect!(&'static HelperProc);
+
 fn strip_path(p: String) -> String {
 	p.replace("/proc/", "/").replace("/verb/", "/")
 }
 
 pub fn populate_procs() {
-	let mut i: u32 = 0;
-	loop {
-		let proc = Proc::from_id(raw_types::procs::ProcId(i));
-		if proc.is_none() {
-			break;
-		}
-		let proc = proc.unwrap();
-
+	for proc in Proc::all() {
 		PROC_OVERRIDE_IDS.with(|override_ids| {
 			let mut override_ids = override_ids.borrow_mut();
 
@@ -205,14 +429,30 @@ pub fn populate_procs() {
 				};
 			});
 		});
+	}
 
-		i += 1;
+	for helper in inventory::iter::<&'static HelperProc> {
+		unsafe {
+			*helper.1.get() = get_proc(helper.0);
+		}
 	}
 }
 
 pub fn clear_procs() {
 	PROCS_BY_NAME.with(|h| h.borrow_mut().clear());
 	PROC_OVERRIDE_IDS.with(|override_ids| override_ids.borrow_mut().clear());
+
+	for helper in inventory::iter::<&'static HelperProc> {
+		unsafe {
+			*helper.1.get() = None;
+		}
+	}
+}
+
+/// Throws away our proc lookup tables and rebuilds them from BYOND's current proc array.
+pub fn refresh_procs() {
+	clear_procs();
+	populate_procs();
 }
 
 pub fn get_proc_override<S: Into<String>>(path: S, override_id: u32) -> Option<Proc> {
@@ -0,0 +1,86 @@
+//! A simple profiler that counts calls to each proc and how long they took.
+//!
+//! Only calls that reach BYOND's original implementation are timed, so hooked procs won't show up.
+//! The time measured is until the call returns to its caller, which for a proc that sleeps is the
+//! first time it sleeps rather than when it finishes.
+use crate::raw_types::procs::ProcId;
+use crate::Proc;
+use fxhash::FxHashMap;
+use std::cell::RefCell;
+use std::time::Duration;
+
+extern "C" {
+	// Checked by call_proc_by_id_hook_trampoline before it bothers timing anything
+	static mut profiler_enabled: bool;
+}
+
+#[derive(Default, Clone, Copy)]
+struct Counters {
+	calls: u64,
+	total_ns: u64,
+}
+
+// Every proc call happens on BYOND's main thread, so there's nothing to contend on here.
+thread_local! {
+	static COUNTERS: RefCell<FxHashMap<ProcId, Counters>> = RefCell::new(FxHashMap::default());
+}
+
+pub struct ProcStats {
+	pub proc: Proc,
+	pub calls: u64,
+	pub total_time: Duration,
+}
+
+/// Throws away any previous results and starts timing proc calls.
+pub fn start() {
+	COUNTERS.with(|c| c.borrow_mut().clear());
+	unsafe {
+		profiler_enabled = true;
+	}
+}
+
+/// Stops timing proc calls. The results so far are kept for [report].
+pub fn stop() {
+	unsafe {
+		profiler_enabled = false;
+	}
+}
+
+pub fn is_running() -> bool {
+	unsafe { profiler_enabled }
+}
+
+/// Returns the stats collected so far, most expensive procs first.
+pub fn report() -> Vec<ProcStats> {
+	let mut stats: Vec<ProcStats> = COUNTERS.with(|c| {
+		c.borrow()
+			.iter()
+			.filter_map(|(id, counters)| {
+				Some(ProcStats {
+					proc: Proc::from_id(*id)?,
+					calls: counters.calls,
+					total_time: Duration::from_nanos(counters.total_ns),
+				})
+			})
+			.collect()
+	});
+
+	stats.sort_unstable_by(|a, b| b.total_time.cmp(&a.total_time));
+	stats
+}
+
+// Proc IDs are meaningless after a reload, so don't carry anything over
+pub(crate) fn shutdown() {
+	stop();
+	COUNTERS.with(|c| c.borrow_mut().clear());
+}
+
+#[no_mangle]
+extern "C" fn on_proc_call_timed(proc_id: ProcId, nanoseconds: u64) {
+	COUNTERS.with(|c| {
+		let mut counters = c.borrow_mut();
+		let entry = counters.entry(proc_id).or_default();
+		entry.calls += 1;
+		entry.total_ns += nanoseconds;
+	});
+}
@@ -50,9 +50,9 @@ pub struct ExecutionContext {
 	unk_1: [u8; 0x10],
 	pub dot: values::Value,
 	pub locals: *mut values::Value,
-	stack: *mut values::Value,
+	pub stack: *mut values::Value,
 	pub locals_count: u16,
-	stack_size: u16,
+	pub stack_size: u16,
 	unk_2: u32,
 	current_iterator: *mut values::Value,
 	iterator_allocated: u32,
@@ -1,6 +1,7 @@
 use super::funcs;
 use super::lists;
 use super::strings;
+use std::convert::TryFrom;
 use std::ffi::CStr;
 use std::fmt;
 
@@ -66,6 +67,68 @@ pub enum ValueTag {
 	Appearance = 0x3A,
 }
 
+impl TryFrom<u8> for ValueTag {
+	type Error = u8;
+
+	/// Fails with the tag itself if it's one auxtools doesn't know about yet.
+	fn try_from(tag: u8) -> Result<Self, Self::Error> {
+		Ok(match tag {
+			0x00 => ValueTag::Null,
+			0x01 => ValueTag::Turf,
+			0x02 => ValueTag::Obj,
+			0x03 => ValueTag::Mob,
+			0x04 => ValueTag::Area,
+			0x05 => ValueTag::Client,
+			0x06 => ValueTag::String,
+			0x08 => ValueTag::MobTypepath,
+			0x09 => ValueTag::ObjTypepath,
+			0x0A => ValueTag::TurfTypepath,
+			0x0B => ValueTag::AreaTypepath,
+			0x0C => ValueTag::Resource,
+			0x0D => ValueTag::Image,
+			0x0E => ValueTag::World,
+			0x0F => ValueTag::List,
+			0x10 => ValueTag::ArgList,
+			0x17 => ValueTag::MobContents,
+			0x18 => ValueTag::TurfContents,
+			0x19 => ValueTag::AreaContents,
+			0x1A => ValueTag::WorldContents,
+			0x1C => ValueTag::ObjContents,
+			0x2C => ValueTag::MobVars,
+			0x2D => ValueTag::ObjVars,
+			0x2E => ValueTag::TurfVars,
+			0x2F => ValueTag::AreaVars,
+			0x30 => ValueTag::ClientVars,
+			0x31 => ValueTag::Vars,
+			0x32 => ValueTag::MobOverlays,
+			0x33 => ValueTag::MobUnderlays,
+			0x34 => ValueTag::ObjOverlays,
+			0x35 => ValueTag::ObjUnderlays,
+			0x36 => ValueTag::TurfOverlays,
+			0x37 => ValueTag::TurfUnderlays,
+			0x38 => ValueTag::AreaOverlays,
+			0x39 => ValueTag::AreaUnderlays,
+			0x40 => ValueTag::ImageOverlays,
+			0x41 => ValueTag::ImageUnderlays,
+			0x42 => ValueTag::ImageVars,
+			0x4B => ValueTag::TurfVisContents,
+			0x4C => ValueTag::ObjVisContents,
+			0x4D => ValueTag::MobVisContents,
+			0x4E => ValueTag::TurfVisLocs,
+			0x4F => ValueTag::ObjVisLocs,
+			0x50 => ValueTag::MobVisLocs,
+			0x51 => ValueTag::WorldVars,
+			0x52 => ValueTag::GlobalVars,
+			0x54 => ValueTag::ImageVisContents,
+			0x21 => ValueTag::Datum,
+			0x23 => ValueTag::SaveFile,
+			0x2A => ValueTag::Number,
+			0x3A => ValueTag::Appearance,
+			_ => return Err(tag),
+		})
+	}
+}
+
 impl fmt::Display for Value {
 	fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
 		unsafe {
@@ -104,8 +167,7 @@ impl fmt::Debug for Value {
 
 impl fmt::Display for ValueTag {
 	fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
-		//write!(f, "{:?}", self)
-		write!(f, "TODO")
+		write!(f, "{:?}", self)
 	}
 }
 
@@ -125,3 +187,22 @@ pub struct Value {
 	pub tag: ValueTag,
 	pub data: ValueData,
 }
+
+#[cfg(test)]
+mod tests {
+	use super::ValueTag;
+	use std::convert::TryFrom;
+
+	#[test]
+	fn tags_round_trip() {
+		for raw in 0..=u8::MAX {
+			match ValueTag::try_from(raw) {
+				Ok(tag) => assert_eq!(tag as u8, raw),
+				Err(unknown) => assert_eq!(unknown, raw),
+			}
+		}
+
+		assert_eq!(ValueTag::try_from(0x2A), Ok(ValueTag::Number));
+		assert_eq!(ValueTag::try_from(0x07), Err(0x07));
+	}
+}
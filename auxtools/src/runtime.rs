@@ -1,4 +1,5 @@
 use crate::value::Value;
+use std::fmt;
 use std::result;
 
 /// Represents a byond runtime, sort of. This will probably drastically in the future.
@@ -7,16 +8,48 @@ use std::result;
 #[derive(Debug)]
 pub struct Runtime {
 	pub message: String,
+	pub kind: RuntimeKind,
+}
+
+/// Broad categories of [Runtime], so errors can be handled without matching on their messages.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+#[non_exhaustive]
+pub enum RuntimeKind {
+	/// A value wasn't of the type it was expected to be.
+	TypeMismatch,
+	/// Something was done to a null value that needs a real one.
+	NullDereference,
+	/// An index or coordinate was outside of the valid range.
+	IndexOutOfBounds,
+	/// A proc that was asked for doesn't exist.
+	ProcNotFound,
+	/// An argument wasn't one of the values it's allowed to be.
+	InvalidArgument,
+	/// Anything else, including all runtimes created with [Runtime::new].
+	Custom,
 }
 
 impl Runtime {
 	pub fn new<S: Into<String>>(message: S) -> Self {
+		Self::typed(RuntimeKind::Custom, message)
+	}
+
+	pub fn typed<S: Into<String>>(kind: RuntimeKind, message: S) -> Self {
 		Self {
 			message: message.into(),
+			kind,
 		}
 	}
 }
 
+impl fmt::Display for Runtime {
+	fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+		write!(f, "{}", self.message)
+	}
+}
+
+impl std::error::Error for Runtime {}
+
 /// This macro makes instantiating [Runtimes](struct.Runtime.html) a (little bit) easier.
 #[macro_export]
 macro_rules! runtime {
@@ -1,3 +1,4 @@
+mod cache;
 #[cfg(unix)]
 mod linux;
 #[cfg(windows)]
@@ -11,6 +12,7 @@ pub use linux::Scanner;
 pub use windows::Scanner;
 
 pub use auxtools_impl::convert_signature;
+pub use cache::{disable_cache, enable_cache};
 
 pub use once_cell;
 
@@ -81,6 +83,17 @@ macro_rules! universal_signature {
 	};
 }
 
+/// Tries each signature in order until one matches, for functions whose bytes differ between BYOND
+/// builds in ways that can't be pinned to a version range.
+#[macro_export]
+macro_rules! candidate_signatures {
+	( $($sig:tt),* ) => {
+		$crate::sigscan::SignatureMap::Candidates(vec![
+			$(signature_struct!($sig),)*
+		])
+	};
+}
+
 #[macro_export]
 macro_rules! find_signature_inner {
 	($scanner:ident, $name:ident, $type:ty) => {
@@ -88,7 +101,13 @@ macro_rules! find_signature_inner {
 		if let Some(ptr) = SIGNATURES0.$name.find(&$scanner, $crate::version::get().1) {
 			$name = ptr as $type;
 		} else {
-			return Some(format!("FAILED (Couldn't find {})", stringify!($name)));
+			return Some(format!(
+				"FAILED (Couldn't find {} in {} for BYOND {}.{})",
+				stringify!($name),
+				$scanner.module_name(),
+				$crate::version::get().0,
+				$crate::version::get().1
+			));
 		}
 	};
 }
@@ -100,7 +119,13 @@ macro_rules! find_signature_inner_result {
 		if let Some(ptr) = SIGNATURES0.$name.find(&$scanner, $crate::version::get().1) {
 			$name = ptr as $type;
 		} else {
-			return Err(format!("FAILED (Couldn't find {})", stringify!($name)));
+			return Err(format!(
+				"FAILED (Couldn't find {} in {} for BYOND {}.{})",
+				stringify!($name),
+				$scanner.module_name(),
+				$crate::version::get().0,
+				$crate::version::get().1
+			));
 		}
 	};
 }
@@ -122,6 +147,21 @@ macro_rules! find_signature {
 
 #[macro_export]
 macro_rules! find_signature_result {
+	// On Linux, an exported symbol is used instead of the signature if there is one
+	($scanner:ident, ($name:ident as $type:ty, symbol $symbol:literal)) => {
+		#[cfg(unix)]
+		let $name: $type = match $scanner.find_symbol($symbol) {
+			Some(ptr) => ptr as $type,
+			None => {
+				find_signature_inner_result!($scanner, $name, $type);
+				$name
+			}
+		};
+
+		#[cfg(windows)]
+		find_signature_inner_result!($scanner, $name, $type);
+	};
+
 	($scanner:ident, $name:ident as $type:ty) => {
 		find_signature_inner_result!($scanner, $name, $type);
 	};
@@ -153,6 +193,165 @@ macro_rules! find_signatures_result {
 	};
 }
 
+// Shared by both platforms' `Scanner::find_all`. `data_end` is inclusive.
+fn find_all_in(data_begin: *mut u8, data_end: *mut u8, signature: &[Option<u8>]) -> Vec<*mut u8> {
+	if signature.is_empty() || data_begin.is_null() || data_end < data_begin {
+		return vec![];
+	}
+
+	let len = data_end as usize - data_begin as usize + 1;
+	let data = unsafe { std::slice::from_raw_parts(data_begin as *const u8, len) };
+
+	// Every candidate start is checked exactly once, bailing at the first mismatched byte.
+	data.windows(signature.len())
+		.enumerate()
+		.filter(|(_, window)| {
+			signature
+				.iter()
+				.zip(window.iter())
+				.all(|(expected, actual)| expected.map_or(true, |b| b == *actual))
+		})
+		.map(|(offset, _)| unsafe { data_begin.add(offset) })
+		.collect()
+}
+
+// Shared by both platforms' `Scanner::find_many`. `data_end` is inclusive.
+//
+// The module is walked once, and at each byte only the signatures starting with that byte (or with
+// a wildcard) are checked, so resolving N signatures costs about one pass instead of N. The ignored
+// `find_many_benchmark` test in linux.rs measures this on an 8 MiB buffer of fake x86: one call here
+// for 32 signatures took ~100ms in release builds, against ~270ms for 32 calls to `Scanner::find`.
+fn find_many_in(
+	data_begin: *mut u8,
+	data_end: *mut u8,
+	signatures: &[&[Option<u8>]],
+) -> Vec<Option<*mut u8>> {
+	// (first match, found more than once)
+	let mut results: Vec<(Option<*mut u8>, bool)> = vec![(None, false); signatures.len()];
+
+	if data_begin.is_null() || data_end < data_begin {
+		return vec![None; signatures.len()];
+	}
+
+	let mut by_first_byte: Vec<Vec<usize>> = vec![vec![]; 256];
+	let mut wildcard_first = vec![];
+	for (idx, signature) in signatures.iter().enumerate() {
+		match signature.first() {
+			Some(Some(byte)) => by_first_byte[*byte as usize].push(idx),
+			Some(None) => wildcard_first.push(idx),
+			None => {}
+		}
+	}
+
+	let len = data_end as usize - data_begin as usize + 1;
+	let data = unsafe { std::slice::from_raw_parts(data_begin as *const u8, len) };
+
+	for offset in 0..len {
+		let candidates = by_first_byte[data[offset] as usize]
+			.iter()
+			.chain(wildcard_first.iter());
+
+		for &idx in candidates {
+			let signature = signatures[idx];
+			let (found, ambiguous) = &mut results[idx];
+			if *ambiguous || offset + signature.len() > len {
+				continue;
+			}
+
+			let matches = signature
+				.iter()
+				.zip(data[offset..].iter())
+				.all(|(expected, actual)| expected.map_or(true, |b| b == *actual));
+
+			if matches {
+				if found.is_some() {
+					*ambiguous = true;
+				} else {
+					*found = Some(unsafe { data_begin.add(offset) });
+				}
+			}
+		}
+	}
+
+	// Same as `find`: a signature that matches more than once isn't trusted
+	results
+		.into_iter()
+		.map(|(found, ambiguous)| if ambiguous { None } else { found })
+		.collect()
+}
+
+/// Why [convert_signature_runtime] couldn't parse a signature.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct ParseError {
+	/// Offset of the offending byte in the input, in characters.
+	pub offset: usize,
+	pub message: String,
+}
+
+impl std::fmt::Display for ParseError {
+	fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+		write!(f, "{} at offset {}", self.message, self.offset)
+	}
+}
+
+impl std::error::Error for ParseError {}
+
+/// Parses a signature like `"55 8B EC ?? 6A"` at runtime, for signatures that aren't known at compile time.
+///
+/// Bytes are two hex digits separated by any amount of whitespace, and `??` or `?` is a wildcard.
+pub fn convert_signature_runtime(signature: &str) -> Result<Vec<Option<u8>>, ParseError> {
+	let mut bytes = vec![];
+	let mut chars = signature.char_indices().peekable();
+
+	while let Some(&(start, _)) = chars.peek() {
+		let mut token = String::new();
+		while let Some(&(_, c)) = chars.peek() {
+			if c.is_whitespace() {
+				break;
+			}
+			token.push(c);
+			chars.next();
+		}
+
+		while chars.peek().map_or(false, |(_, c)| c.is_whitespace()) {
+			chars.next();
+		}
+
+		if token.is_empty() {
+			continue;
+		}
+
+		let offset = signature[..start].chars().count();
+		match token.as_str() {
+			"?" | "??" => bytes.push(None),
+			_ if token.len() == 2 => match u8::from_str_radix(&token, 16) {
+				Ok(byte) => bytes.push(Some(byte)),
+				Err(_) => {
+					return Err(ParseError {
+						offset,
+						message: format!("{:?} isn't a hex byte", token),
+					})
+				}
+			},
+			_ => {
+				return Err(ParseError {
+					offset,
+					message: format!("expected a hex byte or wildcard, got {:?}", token),
+				})
+			}
+		}
+	}
+
+	if bytes.is_empty() {
+		return Err(ParseError {
+			offset: 0,
+			message: "signature is empty".to_owned(),
+		});
+	}
+
+	Ok(bytes)
+}
+
 pub enum SignatureTreatment {
 	NoOffset,
 	OffsetByInt(isize),
@@ -166,7 +365,18 @@ pub struct Signature {
 
 impl Signature {
 	pub fn find(&self, scanner: &Scanner) -> Option<*const std::ffi::c_void> {
-		scanner.find(&self.bytes).map(|address| unsafe {
+		let found = cache::find(scanner, self.bytes);
+		match found {
+			Some(address) => aux_log!(
+				debug,
+				"Found signature in {} at {:p}",
+				scanner.module_name(),
+				address
+			),
+			None => aux_log!(debug, "Signature not found in {}", scanner.module_name()),
+		}
+
+		found.map(|address| unsafe {
 			match self.treatment {
 				SignatureTreatment::NoOffset | SignatureTreatment::OffsetByInt(0) => {
 					std::mem::transmute(address as *const std::ffi::c_void)
@@ -186,6 +396,8 @@ impl Signature {
 pub enum SignatureMap {
 	AllVersions(Signature),
 	VersionDependent(Vec<((Bound<&'static u32>, Bound<&'static u32>), Signature)>),
+	/// Signatures to try in order, regardless of version. The first one that matches wins.
+	Candidates(Vec<Signature>),
 }
 
 impl SignatureMap {
@@ -196,6 +408,70 @@ impl SignatureMap {
 				.iter()
 				.find(|(version_range, _)| version_range.contains(&version))
 				.and_then(|(_, signature)| signature.find(scanner)),
+			Self::Candidates(signatures) => signatures
+				.iter()
+				.find_map(|signature| signature.find(scanner)),
 		}
 	}
 }
+
+#[cfg(test)]
+mod tests {
+	#[test]
+	fn find_all_in_returns_overlapping_matches() {
+		let mut data = [0x55u8, 0x8B, 0x55, 0x8B, 0x55, 0x00];
+		let begin = data.as_mut_ptr();
+		let end = unsafe { begin.add(data.len() - 1) };
+
+		let matches = super::find_all_in(begin, end, &[Some(0x55), None, Some(0x55)]);
+		assert_eq!(matches, vec![begin, unsafe { begin.add(2) }]);
+
+		assert!(super::find_all_in(begin, end, &[Some(0x12)]).is_empty());
+	}
+
+	#[test]
+	fn convert_signature_runtime_parses_leniently() {
+		assert_eq!(
+			super::convert_signature_runtime("  55 8b\tEC ?? ?\n6A ").unwrap(),
+			vec![Some(0x55), Some(0x8B), Some(0xEC), None, None, Some(0x6A)]
+		);
+
+		let error = super::convert_signature_runtime("55 8G EC").unwrap_err();
+		assert_eq!(error.offset, 3);
+
+		let error = super::convert_signature_runtime("55 ECX").unwrap_err();
+		assert_eq!(error.offset, 3);
+
+		assert!(super::convert_signature_runtime("   ").is_err());
+	}
+
+	#[test]
+	fn find_many_in_matches_find_semantics() {
+		let mut data = [0x55u8, 0x8B, 0xEC, 0x55, 0x8B, 0x90, 0xC3];
+		let begin = data.as_mut_ptr();
+		let end = unsafe { begin.add(data.len() - 1) };
+
+		let unique: &[Option<u8>] = &[Some(0x8B), Some(0xEC)];
+		let ambiguous: &[Option<u8>] = &[Some(0x55), Some(0x8B)];
+		let wildcard: &[Option<u8>] = &[None, Some(0x90), Some(0xC3)];
+		let missing: &[Option<u8>] = &[Some(0x12)];
+		let past_end: &[Option<u8>] = &[Some(0xC3), None];
+
+		let results = super::find_many_in(
+			begin,
+			end,
+			&[unique, ambiguous, wildcard, missing, past_end, &[]],
+		);
+		assert_eq!(
+			results,
+			vec![
+				Some(unsafe { begin.add(1) }),
+				None,
+				Some(unsafe { begin.add(4) }),
+				None,
+				None,
+				None
+			]
+		);
+	}
+}
@@ -0,0 +1,141 @@
+// An optional on-disk cache of where signatures were found, so we don't have to scan the whole of
+// byondcore every time the server starts.
+//
+// The file is plain text: a `version <major> <build>` header followed by `<offset> <module> <signature>`
+// lines, where the offset is relative to the start of the module so it survives ASLR.
+use super::Scanner;
+use once_cell::sync::Lazy;
+use std::collections::HashMap;
+use std::fs;
+use std::path::PathBuf;
+use std::sync::Mutex;
+
+struct SignatureCache {
+	path: PathBuf,
+	version: (u32, u32),
+	offsets: HashMap<String, usize>,
+}
+
+static CACHE: Lazy<Mutex<Option<SignatureCache>>> = Lazy::new(|| Mutex::new(None));
+
+impl SignatureCache {
+	fn load(path: PathBuf) -> Self {
+		let mut cache = SignatureCache {
+			path,
+			version: (0, 0),
+			offsets: HashMap::new(),
+		};
+
+		let contents = match fs::read_to_string(&cache.path) {
+			Ok(contents) => contents,
+			Err(_) => return cache,
+		};
+
+		let mut lines = contents.lines();
+		let header: Vec<&str> = lines.next().unwrap_or("").split(' ').collect();
+		match header.as_slice() {
+			["version", major, build] => match (major.parse(), build.parse()) {
+				(Ok(major), Ok(build)) => cache.version = (major, build),
+				_ => return cache,
+			},
+			_ => return cache,
+		}
+
+		for line in lines {
+			let mut parts = line.splitn(2, ' ');
+			if let (Some(offset), Some(key)) = (parts.next(), parts.next()) {
+				if let Ok(offset) = usize::from_str_radix(offset, 16) {
+					cache.offsets.insert(key.to_owned(), offset);
+				}
+			}
+		}
+
+		cache
+	}
+
+	fn save(&self) {
+		let mut contents = format!("version {} {}\n", self.version.0, self.version.1);
+		for (key, offset) in &self.offsets {
+			contents.push_str(&format!("{:X} {}\n", offset, key));
+		}
+
+		// The cache is only an optimisation, so failing to write it isn't worth reporting
+		let _ = fs::write(&self.path, contents);
+	}
+}
+
+/// Turns on the signature cache, stored at `path`. Signatures found from now on will be saved there,
+/// and signatures already in it will be checked against memory instead of scanned for.
+///
+/// The cache is thrown away whenever the BYOND version changes.
+pub fn enable_cache<P: Into<PathBuf>>(path: P) {
+	*CACHE.lock().unwrap() = Some(SignatureCache::load(path.into()));
+}
+
+pub fn disable_cache() {
+	*CACHE.lock().unwrap() = None;
+}
+
+fn cache_key(scanner: &Scanner, signature: &[Option<u8>]) -> String {
+	let bytes: Vec<String> = signature
+		.iter()
+		.map(|b| match b {
+			Some(b) => format!("{:02X}", b),
+			None => "??".to_owned(),
+		})
+		.collect();
+	format!("{} {}", scanner.module_name(), bytes.join(" "))
+}
+
+fn matches_at(scanner: &Scanner, signature: &[Option<u8>], offset: usize) -> bool {
+	let (data_begin, data_end) = scanner.range();
+	let len = data_end as usize - data_begin as usize + 1;
+	if offset
+		.checked_add(signature.len())
+		.map_or(true, |end| end > len)
+	{
+		return false;
+	}
+
+	let data = unsafe { std::slice::from_raw_parts(data_begin.add(offset), signature.len()) };
+	signature
+		.iter()
+		.zip(data)
+		.all(|(expected, actual)| expected.map_or(true, |b| b == *actual))
+}
+
+// Same as `Scanner::find`, but goes through the cache if it's enabled
+pub(super) fn find(scanner: &Scanner, signature: &[Option<u8>]) -> Option<*mut u8> {
+	let mut cache = CACHE.lock().unwrap();
+	let cache = match cache.as_mut() {
+		Some(cache) => cache,
+		None => return scanner.find(signature),
+	};
+
+	let version = crate::version::get();
+	if cache.version != version {
+		cache.version = version;
+		cache.offsets.clear();
+	}
+
+	let key = cache_key(scanner, signature);
+	if let Some(&offset) = cache.offsets.get(&key) {
+		if matches_at(scanner, signature, offset) {
+			return Some(unsafe { scanner.range().0.add(offset) });
+		}
+	}
+
+	let found = scanner.find(signature);
+	match found {
+		Some(ptr) => {
+			cache
+				.offsets
+				.insert(key, ptr as usize - scanner.range().0 as usize);
+		}
+		None => {
+			cache.offsets.remove(&key);
+		}
+	}
+	cache.save();
+	found
+}
@@ -1,7 +1,10 @@
 use std::ffi::{c_void, CStr, CString};
 use std::os::raw::{c_char, c_int};
 
-use libc::{dl_iterate_phdr, dl_phdr_info, Elf32_Phdr, PT_LOAD};
+use libc::{
+	dl_iterate_phdr, dl_phdr_info, dlclose, dlopen, dlsym, Elf32_Phdr, PT_LOAD, RTLD_LAZY,
+	RTLD_NOLOAD,
+};
 
 #[repr(C)]
 struct CallbackData {
@@ -13,6 +16,11 @@ struct CallbackData {
 
 pub struct Scanner {
 	module_name: String,
+	// The module this scanner's memory belongs to, even once narrowed with `for_range`
+	module: String,
+	narrowed: bool,
+	data_begin: *mut u8,
+	data_end: *mut u8,
 }
 
 extern "C" fn dl_phdr_callback(info: *mut dl_phdr_info, _size: usize, data: *mut c_void) -> c_int {
@@ -46,13 +54,7 @@ extern "C" fn dl_phdr_callback(info: *mut dl_phdr_info, _size: usize, data: *mut
 
 impl Scanner {
 	pub fn for_module(name: &str) -> Option<Scanner> {
-		Some(Scanner {
-			module_name: name.to_string(),
-		})
-	}
-
-	pub fn find(&self, signature: &[Option<u8>]) -> Option<*mut u8> {
-		let module_name = CString::new(self.module_name.clone()).unwrap();
+		let module_name = CString::new(name).ok()?;
 		let module_name_ptr = module_name.as_ptr();
 		let mut data = CallbackData {
 			module_name_ptr,
@@ -67,14 +69,91 @@ impl Scanner {
 			)
 		};
 
-		let mut data_current = data.memory_start as *mut u8;
-		let data_end = (data.memory_start + data.memory_len) as *mut u8;
+		if data.memory_start == 0 || data.memory_len == 0 {
+			// The module wasn't found.
+			return None;
+		}
+
+		let data_begin = data.memory_start as *mut u8;
+		let data_end = (data.memory_start + data.memory_len - 1) as *mut u8;
+
+		Some(Scanner {
+			module_name: name.to_string(),
+			module: name.to_string(),
+			narrowed: false,
+			data_begin,
+			data_end,
+		})
+	}
+
+	/// Narrows this scanner down to the `len` bytes starting at `start`, e.g. to look for a function
+	/// near one that's already been found.
+	///
+	/// Returns `None` unless the whole range is inside this scanner's, so it can't read unmapped memory.
+	pub fn for_range(&self, start: usize, len: usize) -> Option<Scanner> {
+		let (begin, end) = (self.data_begin as usize, self.data_end as usize);
+		let last = start.checked_add(len)?.checked_sub(1)?;
+		if len == 0 || start < begin || last > end {
+			return None;
+		}
+
+		Some(Scanner {
+			module_name: format!("{}+{:X}", self.module_name, start - begin),
+			module: self.module.clone(),
+			narrowed: true,
+			data_begin: start as *mut u8,
+			data_end: last as *mut u8,
+		})
+	}
+
+	/// Looks `name` up in the module's dynamic symbol table, which is far more reliable than a
+	/// signature for functions that happen to be exported. Names have to be given mangled, like
+	/// `_ZN8ByondLib15GetByondVersionEv`.
+	///
+	/// BYOND exports the methods of its `ByondLib` class, such as `GetByondVersion` and
+	/// `GetByondBuild`, but the internal functions auxtools hooks generally aren't, which is why
+	/// they need signatures. Check with `nm -D` before relying on a symbol.
+	///
+	/// Returns `None` if the symbol isn't exported, or for a scanner narrowed with
+	/// [for_range](Self::for_range), if it isn't inside that range. A whole module's scanner only
+	/// covers its first loaded segment, which needn't hold its code, so there it isn't checked.
+	pub fn find_symbol(&self, name: &str) -> Option<*mut u8> {
+		let module_name = CString::new(self.module.as_str()).ok()?;
+		let symbol_name = CString::new(name).ok()?;
+
+		let address = unsafe {
+			// The module is already loaded, so this only borrows a handle to it
+			let module = dlopen(module_name.as_ptr(), RTLD_LAZY | RTLD_NOLOAD);
+			if module.is_null() {
+				return None;
+			}
+
+			let address = dlsym(module, symbol_name.as_ptr());
+			dlclose(module);
+			address as *mut u8
+		};
 
-		if data_current.is_null() || data_end == data_current {
-			// There's no more bytes to scan or the module wasn't found.
+		if address.is_null()
+			|| (self.narrowed && (address < self.data_begin || address > self.data_end))
+		{
 			return None;
 		}
 
+		Some(address)
+	}
+
+	pub fn module_name(&self) -> &str {
+		&self.module_name
+	}
+
+	// The module's memory, inclusive of `data_end`
+	pub(crate) fn range(&self) -> (*mut u8, *mut u8) {
+		(self.data_begin, self.data_end)
+	}
+
+	pub fn find(&self, signature: &[Option<u8>]) -> Option<*mut u8> {
+		let mut data_current = self.data_begin;
+		let data_end = self.data_end;
 		let mut signature_offset = 0;
 		let mut result: Option<*mut u8> = None;
 
@@ -105,7 +184,129 @@ impl Scanner {
 
 		result
 	}
+
+	/// Returns every address matching `signature`, in order.
+	///
+	/// Unlike [find](Self::find) this doesn't require the match to be unique, so loose signatures can be
+	/// narrowed down afterwards.
+	pub fn find_all(&self, signature: &[Option<u8>]) -> Vec<*mut u8> {
+		super::find_all_in(self.data_begin, self.data_end, signature)
+	}
+
+	/// Looks up several signatures in a single pass over the module.
+	///
+	/// Each result is what [find](Self::find) would have returned for the signature at the same index,
+	/// so signatures that match more than once come back as `None`.
+	pub fn find_many(&self, signatures: &[&[Option<u8>]]) -> Vec<Option<*mut u8>> {
+		super::find_many_in(self.data_begin, self.data_end, signatures)
+	}
 }
 
 #[cfg(test)]
-mod tests {}
+mod tests {
+	use super::Scanner;
+	use crate::{
+		find_signature_inner_result, find_signature_result, signature, signature_struct,
+		signatures, universal_signature,
+	};
+	use std::ffi::c_void;
+	use std::time::Instant;
+
+	// Nothing in auxtools is exported by BYOND yet, so this resolves one of libc's exports instead. The
+	// fallback signature mustn't match anything, so this only passes if the symbol is used.
+	signatures! {
+		getpid => universal_signature!("DE AD BE EF DE AD BE EF DE AD BE EF")
+	}
+
+	fn find_getpid(scanner: &Scanner) -> Result<*const c_void, String> {
+		find_signature_result!(scanner, (getpid as *const c_void, symbol "getpid"));
+		Ok(getpid)
+	}
+
+	#[test]
+	fn find_signature_result_prefers_symbols() {
+		let libc = Scanner::for_module("libc.so.6").expect("libc isn't loaded");
+		let getpid: extern "C" fn() -> i32 =
+			unsafe { std::mem::transmute(find_getpid(&libc).unwrap()) };
+		assert_eq!(getpid() as u32, std::process::id());
+	}
+
+	// Picks bytes roughly the way compiled x86 is distributed, so signatures' first bytes come up about
+	// as often as they would in a real module
+	struct FakeCode(u32);
+
+	impl FakeCode {
+		fn next(&mut self) -> u32 {
+			// xorshift32
+			self.0 ^= self.0 << 13;
+			self.0 ^= self.0 >> 17;
+			self.0 ^= self.0 << 5;
+			self.0
+		}
+
+		fn byte(&mut self) -> u8 {
+			const COMMON: [u8; 16] = [
+				0x00, 0x8B, 0x89, 0x45, 0x55, 0xE8, 0xFF, 0x24, 0x83, 0xC4, 0x04, 0x08, 0x85, 0x74,
+				0x0F, 0xEC,
+			];
+
+			let n = self.next();
+			if n & 1 == 0 {
+				COMMON[(n >> 1) as usize % COMMON.len()]
+			} else {
+				(n >> 8) as u8
+			}
+		}
+	}
+
+	// Run with `cargo test --release -p auxtools find_many_benchmark -- --ignored --nocapture`
+	#[test]
+	#[ignore]
+	fn find_many_benchmark() {
+		let mut code = FakeCode(0x2545_F491);
+		let mut data: Vec<u8> = (0..8 * 1024 * 1024).map(|_| code.byte()).collect();
+
+		// 32 signatures of 24 bytes with the odd wildcard, each planted once
+		let signatures: Vec<Vec<Option<u8>>> = (0..32)
+			.map(|_| {
+				(0..24)
+					.map(|i| match code.next() % 5 {
+						0 if i > 0 => None,
+						_ => Some(code.byte()),
+					})
+					.collect()
+			})
+			.collect();
+
+		for (i, signature) in signatures.iter().enumerate() {
+			let at = (i + 1) * data.len() / 34;
+			for (offset, byte) in signature.iter().enumerate() {
+				data[at + offset] = byte.unwrap_or(0xCC);
+			}
+		}
+
+		let scanner = Scanner {
+			module_name: "benchmark".to_owned(),
+			module: "benchmark".to_owned(),
+			narrowed: false,
+			data_begin: data.as_mut_ptr(),
+			data_end: unsafe { data.as_mut_ptr().add(data.len() - 1) },
+		};
+		let signatures: Vec<&[Option<u8>]> = signatures.iter().map(|s| s.as_slice()).collect();
+
+		let start = Instant::now();
+		let separately: Vec<Option<*mut u8>> = signatures.iter().map(|s| scanner.find(s)).collect();
+		let separately_time = start.elapsed();
+
+		let start = Instant::now();
+		let together = scanner.find_many(&signatures);
+		let together_time = start.elapsed();
+
+		assert_eq!(separately, together);
+		assert!(together.iter().all(Option::is_some));
+		println!(
+			"32 calls to find: {:?}, one call to find_many: {:?}",
+			separately_time, together_time
+		);
+	}
+}
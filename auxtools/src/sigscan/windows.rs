@@ -7,6 +7,7 @@ use winapi::um::processthreadsapi;
 use winapi::um::psapi;
 
 pub struct Scanner {
+	module_name: String,
 	_module: minwindef::HMODULE,
 	data_begin: *mut u8,
 	data_end: *mut u8,
@@ -46,12 +47,41 @@ impl Scanner {
 		}
 
 		Some(Scanner {
+			module_name: name.to_string(),
 			_module: module,
 			data_begin,
 			data_end,
 		})
 	}
 
+	/// Narrows this scanner down to the `len` bytes starting at `start`, e.g. to look for a function
+	/// near one that's already been found.
+	///
+	/// Returns `None` unless the whole range is inside this scanner's, so it can't read unmapped memory.
+	pub fn for_range(&self, start: usize, len: usize) -> Option<Scanner> {
+		let (begin, end) = (self.data_begin as usize, self.data_end as usize);
+		let last = start.checked_add(len)?.checked_sub(1)?;
+		if len == 0 || start < begin || last > end {
+			return None;
+		}
+
+		Some(Scanner {
+			module_name: format!("{}+{:X}", self.module_name, start - begin),
+			_module: self._module,
+			data_begin: start as *mut u8,
+			data_end: last as *mut u8,
+		})
+	}
+
+	pub fn module_name(&self) -> &str {
+		&self.module_name
+	}
+
+	// The module's memory, inclusive of `data_end`
+	pub(crate) fn range(&self) -> (*mut u8, *mut u8) {
+		(self.data_begin, self.data_end)
+	}
+
 	pub fn find(&self, signature: &[Option<u8>]) -> Option<*mut u8> {
 		let mut data_current = self.data_begin;
 		let data_end = self.data_end;
@@ -85,6 +115,22 @@ impl Scanner {
 
 		result
 	}
+
+	/// Returns every address matching `signature`, in order.
+	///
+	/// Unlike [find](Self::find) this doesn't require the match to be unique, so loose signatures can be
+	/// narrowed down afterwards.
+	pub fn find_all(&self, signature: &[Option<u8>]) -> Vec<*mut u8> {
+		super::find_all_in(self.data_begin, self.data_end, signature)
+	}
+
+	/// Looks up several signatures in a single pass over the module.
+	///
+	/// Each result is what [find](Self::find) would have returned for the signature at the same index,
+	/// so signatures that match more than once come back as `None`.
+	pub fn find_many(&self, signatures: &[&[Option<u8>]]) -> Vec<Option<*mut u8>> {
+		super::find_many_in(self.data_begin, self.data_end, signatures)
+	}
 }
 
 impl Drop for Scanner {
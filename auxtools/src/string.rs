@@ -8,6 +8,7 @@ pub struct StringRef {
 }
 
 impl StringRef {
+	/// Interns `string` in BYOND's string table, or finds it if it's already there.
 	pub fn new(string: &str) -> DMResult<Self> {
 		Ok(StringRef {
 			value: Value::from_string(string)?,
@@ -40,6 +41,20 @@ impl StringRef {
 		}
 	}
 
+	/// Like [from_id](Self::from_id), but returns `None` for ids that aren't in BYOND's string table.
+	///
+	/// The table is looked up each time, so this is safe to use with ids from bytecode or saved state.
+	pub fn try_from_id(id: raw_types::strings::StringId) -> Option<Self> {
+		unsafe {
+			let mut entry: *mut raw_types::strings::StringEntry = std::ptr::null_mut();
+			if raw_types::funcs::get_string_table_entry(&mut entry, id) != 1 || entry.is_null() {
+				return None;
+			}
+
+			Some(Self::from_id(id))
+		}
+	}
+
 	pub unsafe fn from_variable_id(id: raw_types::strings::VariableId) -> Self {
 		let string_id = *((*raw_types::funcs::VARIABLE_NAMES)
 			.entries
@@ -10,11 +10,19 @@ use std::marker::PhantomData;
 /// `Value` represents any value a DM variable can hold, such as numbers, strings, datums, etc.
 ///
 /// There's a lot of lifetime shenanigans going on, the gist of it is to just not keep Values around for longer than your hook's execution.
+// Transparent so borrowed raw values can be viewed as Values without touching their ref counts
+#[repr(transparent)]
 pub struct Value {
 	pub raw: raw_types::values::Value,
 	phantom: PhantomData<*mut ()>,
 }
 
+/// Values are equal if they're the same type and refer to the same thing, meaning reference identity
+/// rather than structural equality. Two references to one datum or list are equal, but two separate
+/// lists with the same contents are not - the same as `==` on datums in DM.
+///
+/// Strings are interned by BYOND, so equal text means equal strings. Numbers are compared bit-for-bit
+/// so that this agrees with `Hash`, which means `0` and `-0` are different keys.
 impl PartialEq for Value {
 	fn eq(&self, other: &Self) -> bool {
 		unsafe { self.raw.tag == other.raw.tag && self.raw.data.id == other.raw.data.id }
@@ -57,6 +65,18 @@ impl Value {
 	}
 
 	/// Equivalent to DM's `global.vars`.
+	///
+	/// Global variables are read and written like any other var, and reading one that doesn't exist is an error.
+	/// Like every other `Value`, this is only usable from BYOND's main thread.
+	///
+	/// # Examples
+	///
+	/// This is equivalent to `global.max_players += 1` in DM.
+	/// ```ignore
+	/// let globals = Value::globals();
+	/// let max_players = globals.get_number(byond_string!("max_players"))?;
+	/// globals.set(byond_string!("max_players"), max_players + 1.0)?;
+	/// ```
 	pub fn globals() -> Value {
 		Value {
 			raw: raw_types::values::Value {
@@ -108,7 +128,10 @@ impl Value {
 		if (0..max_x * max_y * max_z).contains(&(id - 1)) {
 			Ok(unsafe { Value::turf_by_id_unchecked(id) })
 		} else {
-			Err(runtime!("Attempted to get tile with invalid ID {}", id))
+			Err(runtime::Runtime::typed(
+				runtime::RuntimeKind::IndexOutOfBounds,
+				format!("Attempted to get tile with invalid ID {}", id),
+			))
 		}
 	}
 
@@ -124,15 +147,52 @@ impl Value {
 		if (0..max_x).contains(&x) && (0..max_y).contains(&y) && (0..max_z).contains(&z) {
 			Ok(unsafe { Value::turf_by_id_unchecked(x + y * max_x + z * max_x * max_y) })
 		} else {
-			Err(runtime!(
-				"Attempted to get out-of-range tile at coords {} {} {}",
-				x + 1,
-				y + 1,
-				z + 1
+			Err(runtime::Runtime::typed(
+				runtime::RuntimeKind::IndexOutOfBounds,
+				format!(
+					"Attempted to get out-of-range tile at coords {} {} {}",
+					x + 1,
+					y + 1,
+					z + 1
+				),
 			))
 		}
 	}
 
+	/// Creates a new datum of the type `type_path` with the given arguments for its `New()`, like
+	/// `new /datum/foo(1, 2)` in DM.
+	///
+	/// auxtools can't reach BYOND's object creation directly, so the game has to define a proc for it:
+	/// ```dm
+	/// /proc/auxtools_new(type_path, list/arguments)
+	///     var/type = text2path(type_path)
+	///     if (!type)
+	///         return null
+	///     return new type(arglist(arguments))
+	/// ```
+	/// The datum is created with a [guarded call](crate::Proc::call_guarded), so that needs
+	/// `/proc/auxtools_call_guarded` as well, and a runtime in `New()` comes back as an `Err`.
+	pub fn new_datum(type_path: &str, args: &[&Value]) -> DMResult {
+		let new = crate::helper_proc!("/proc/auxtools_new")
+			.ok_or_else(|| runtime!("new_datum needs /proc/auxtools_new to be defined"))?;
+
+		let arguments = list::List::new();
+		for arg in args {
+			arguments.append(*arg)?;
+		}
+
+		let datum =
+			new.call_guarded(&[&Value::from_string(type_path)?, &Value::from(arguments)])?;
+		if datum == Value::null() {
+			return Err(runtime::Runtime::typed(
+				runtime::RuntimeKind::InvalidArgument,
+				format!("Couldn't create {}: it isn't a type", type_path),
+			));
+		}
+
+		Ok(datum)
+	}
+
 	fn get_by_id(&self, name_id: raw_types::strings::StringId) -> DMResult {
 		let mut val = raw_types::values::Value {
 			tag: raw_types::values::ValueTag::Null,
@@ -197,11 +257,19 @@ impl Value {
 		Ok(())
 	}
 
+	/// What kind of value this is, for matching on.
+	pub fn tag(&self) -> raw_types::values::ValueTag {
+		self.raw.tag
+	}
+
 	/// Check if the current value is a number and casts it.
 	pub fn as_number(&self) -> DMResult<f32> {
 		match self.raw.tag {
 			raw_types::values::ValueTag::Number => unsafe { Ok(self.raw.data.number) },
-			_ => Err(runtime!("Attempt to interpret non-number value as number")),
+			tag => Err(runtime::Runtime::typed(
+				runtime::RuntimeKind::TypeMismatch,
+				format!("expected number, got {:?}", tag),
+			)),
 		}
 	}
 
@@ -211,7 +279,45 @@ impl Value {
 			raw_types::values::ValueTag::String => unsafe {
 				Ok(string::StringRef::from_id(self.raw.data.string).into())
 			},
-			_ => Err(runtime!("Attempt to interpret non-string value as String")),
+			tag => Err(runtime::Runtime::typed(
+				runtime::RuntimeKind::TypeMismatch,
+				format!("expected string, got {:?}", tag),
+			)),
+		}
+	}
+
+	/// Check if the current value is a number or null and casts it, treating null and 0 as false.
+	pub fn as_bool(&self) -> DMResult<bool> {
+		match self.raw.tag {
+			raw_types::values::ValueTag::Null => Ok(false),
+			raw_types::values::ValueTag::Number => unsafe { Ok(self.raw.data.number != 0.0) },
+			tag => Err(runtime::Runtime::typed(
+				runtime::RuntimeKind::TypeMismatch,
+				format!("expected number or null, got {:?}", tag),
+			)),
+		}
+	}
+
+	/// Reads BYOND's reference count for this value, for tracking down leaks.
+	///
+	/// Only strings are supported for now, as they're the only type whose refcount we know how to find.
+	/// Everything else, including lists and datums, returns `None`. The count includes the reference held
+	/// by this `Value`, and is only a snapshot - it can change as soon as BYOND runs any code.
+	pub fn ref_count(&self) -> Option<u32> {
+		match self.raw.tag {
+			raw_types::values::ValueTag::String => unsafe {
+				let mut entry: *mut raw_types::strings::StringEntry = std::ptr::null_mut();
+				if raw_types::funcs::get_string_table_entry(&mut entry, self.raw.data.string) != 1
+					|| entry.is_null()
+				{
+					return None;
+				}
+				Some((*entry).ref_count)
+			},
+			// TODO: Lists keep their refcount in raw_types::lists::List, but we have no signature for
+			// BYOND's get_list_by_id to find one from its id. Datums need a signature for the datum
+			// table as well, and a layout for its entries.
+			_ => None,
 		}
 	}
 
@@ -220,8 +326,52 @@ impl Value {
 		list::List::from_value(self)
 	}
 
+	/// Equivalent to DM's `self[key]`, for anything BYOND lets you subscript.
+	///
+	/// Unlike [List::get](list::List::get), this works on any value and leaves it up to BYOND to
+	/// decide what indexing means, so special lists like `contents` and `vars` behave exactly like
+	/// they do in DM. Values that can't be indexed return an error.
+	pub fn index<K: Into<Value>>(&self, key: K) -> DMResult {
+		let key = key.into();
+		let mut value = raw_types::values::Value {
+			tag: raw_types::values::ValueTag::Null,
+			data: raw_types::values::ValueData { id: 0 },
+		};
+
+		unsafe {
+			if raw_types::funcs::get_assoc_element(&mut value, self.raw, key.raw) == 1 {
+				return Ok(Value::from_raw_owned(value));
+			}
+		}
+
+		Err(runtime::Runtime::typed(
+			runtime::RuntimeKind::TypeMismatch,
+			format!("couldn't read {}[{}]", self, key),
+		))
+	}
+
+	/// Equivalent to DM's `self[key] = value`. See [Value::index].
+	pub fn index_set<K: Into<Value>, V: Into<Value>>(&self, key: K, value: V) -> DMResult<()> {
+		let key = key.into();
+		let value = value.into();
+
+		unsafe {
+			if raw_types::funcs::set_assoc_element(self.raw, key.raw, value.raw) == 1 {
+				return Ok(());
+			}
+		}
+
+		Err(runtime::Runtime::typed(
+			runtime::RuntimeKind::TypeMismatch,
+			format!("couldn't write {}[{}]", self, key),
+		))
+	}
+
 	/// Calls a method of the value with the given arguments.
 	///
+	/// The proc is looked up on the value's own type like it would be in DM, so overrides are respected.
+	/// Underscores in `procname` are treated as spaces, to allow calling verbs with spaces in their names.
+	///
 	/// # Examples:
 	///
 	/// This example is equivalent to `src.explode(3)` in DM.
@@ -260,7 +410,14 @@ impl Value {
 			}
 		}
 
-		Err(runtime!("External proc call failed"))
+		Err(runtime::Runtime::typed(
+			runtime::RuntimeKind::ProcNotFound,
+			format!(
+				"Couldn't call {}() on {:?}, it isn't a datum or has no such proc",
+				procname.as_ref(),
+				self
+			),
+		))
 	}
 
 	// ugh
@@ -304,17 +461,69 @@ impl Value {
 		}
 	}
 
-	/// Gets the type of the Value as a string
-	pub fn get_type(&self) -> Result<String, runtime::Runtime> {
-		self.get(crate::byond_string!("type"))?.to_string()
+	/// The text DM would show for `"[value]"`, for logging. Unlike [to_string](Self::to_string) this never
+	/// fails; if BYOND can't stringify the value its raw tag and data are shown instead.
+	pub fn to_display_string(&self) -> String {
+		self.to_string().unwrap_or_else(|_| format!("{:?}", self))
+	}
+
+	/// Gets the Value's type, e.g. the typepath `/mob/living`, or `None` if it doesn't have one.
+	pub fn get_type(&self) -> Option<Value> {
+		self.get(crate::byond_string!("type"))
+			.ok()
+			.filter(|typepath| typepath.raw.tag != raw_types::values::ValueTag::Null)
 	}
 
 	/// Checks whether this Value's type is equal to `typepath`.
 	pub fn is_exact_type<S: AsRef<str>>(&self, typepath: S) -> bool {
-		match self.get_type() {
-			Err(_) => false,
-			Ok(my_type) => my_type == typepath.as_ref(),
+		self.get_type()
+			.and_then(|my_type| my_type.to_string().ok())
+			.map_or(false, |my_type| my_type == typepath.as_ref())
+	}
+
+	/// Checks whether this Value is a datum of type `typepath` or one of its subtypes, exactly like
+	/// DM's `istype()`, so types that set `parent_type` are handled too. Values without a type, like
+	/// numbers, strings and null, are never of any type.
+	///
+	/// auxtools can't walk BYOND's type tree itself, so the game has to define a proc for it:
+	/// ```dm
+	/// /proc/auxtools_istype(datum, type_path)
+	///     var/type = text2path(type_path)
+	///     return type && istype(datum, type)
+	/// ```
+	/// If the proc is missing, or `typepath` isn't a type, this returns `false`.
+	pub fn is_type<S: AsRef<str>>(&self, typepath: S) -> bool {
+		if self.get_type().is_none() {
+			return false;
 		}
+
+		let istype = match crate::helper_proc!("/proc/auxtools_istype") {
+			Some(istype) => istype,
+			None => return false,
+		};
+
+		Value::from_string(typepath.as_ref())
+			.and_then(|typepath| istype.call(&[self, &typepath]))
+			.map_or(false, |result| result.is_truthy())
+	}
+
+	/// Compares two values with DM's `==`, so the answer is exactly what DM code would get, including
+	/// from any overloaded `operator==`. Use this to replicate game logic faithfully. Compare with
+	/// `==` in Rust ([PartialEq]) when you only need reference identity, as that never calls into BYOND.
+	///
+	/// auxtools can't reach BYOND's comparison directly, so the game has to define a proc for it:
+	/// ```dm
+	/// /proc/auxtools_equals(a, b)
+	///     return a == b
+	/// ```
+	/// The comparison is a [guarded call](crate::Proc::call_guarded), so it needs
+	/// `/proc/auxtools_call_guarded` as well. If either proc is missing, or the comparison
+	/// runtimes, you get an `Err`.
+	pub fn equals_dm(&self, other: &Value) -> DMResult<bool> {
+		let equals = crate::helper_proc!("/proc/auxtools_equals")
+			.ok_or_else(|| runtime!("equals_dm needs /proc/auxtools_equals to be defined"))?;
+
+		Ok(equals.call_guarded(&[self, other])?.is_truthy())
 	}
 
 	pub fn is_truthy(&self) -> bool {
@@ -370,6 +579,13 @@ impl Value {
 		Value::new(v.tag, v.data)
 	}
 
+	/// Views raw values as `Value`s without changing their reference counts.
+	///
+	/// Only sound as long as BYOND keeps the referenced values alive for the lifetime of the slice.
+	pub(crate) unsafe fn from_raw_slice(v: &[raw_types::values::Value]) -> &[Value] {
+		std::slice::from_raw_parts(v.as_ptr() as *const Value, v.len())
+	}
+
 	/// same as from_raw but does not increment the reference count (assumes we already own this reference)
 	pub unsafe fn from_raw_owned(v: raw_types::values::Value) -> Value {
 		Value {
@@ -377,6 +593,33 @@ impl Value {
 			phantom: PhantomData {},
 		}
 	}
+
+	/// Gives up this value's reference without decrementing it, e.g. to hand it to BYOND.
+	/// The opposite of [from_raw_owned](Self::from_raw_owned).
+	pub fn into_raw(self) -> raw_types::values::Value {
+		let raw = self.raw;
+		std::mem::forget(self);
+		raw
+	}
+
+	/// Adds a reference to this value that nothing owns, keeping it alive until a matching [dec_ref](Self::dec_ref).
+	///
+	/// Every `Value` already holds one reference, taken by [from_raw](Self::from_raw) or [Clone] and
+	/// released on drop, so this is only needed when a raw value escapes somewhere auxtools can't track.
+	///
+	/// # Safety
+	/// Each call must be balanced by exactly one `dec_ref`, or the value leaks.
+	pub unsafe fn inc_ref(&self) {
+		raw_types::funcs::inc_ref_count(self.raw);
+	}
+
+	/// Releases a reference taken by [inc_ref](Self::inc_ref).
+	///
+	/// # Safety
+	/// Releasing a reference nobody took can free the value while it's still in use, including by this `Value`.
+	pub unsafe fn dec_ref(&self) {
+		raw_types::funcs::dec_ref_count(self.raw);
+	}
 }
 
 impl Clone for Value {
@@ -58,6 +58,14 @@ impl From<&Value> for Value {
 	}
 }
 
+// Strings can contain NUL, which BYOND can't store, so this has to be fallible
+impl TryFrom<&str> for Value {
+	type Error = Runtime;
+	fn try_from(s: &str) -> Result<Self, Self::Error> {
+		Value::from_string(s)
+	}
+}
+
 /* List-y helpers */
 
 // This is broken due to https://github.com/rust-lang/rust/issues/50133
@@ -104,8 +112,54 @@ impl<T: Into<Value> + Clone> From<&Vec<T>> for Value {
 	fn from(vec: &Vec<T>) -> Self {
 		let res = List::new();
 		for val in vec {
-			res.append(val.clone());
+			// Appending to a list we've just made doesn't fail
+			let _ = res.append(val.clone());
 		}
 		res.into()
 	}
 }
+
+/// Conversions out of a [Value], used by [proc_hook!](crate::proc_hook) to turn a hook's args into
+/// typed ones.
+pub trait FromValue: Sized {
+	fn from_value(value: &Value) -> Result<Self, Runtime>;
+}
+
+impl FromValue for Value {
+	fn from_value(value: &Value) -> Result<Self, Runtime> {
+		Ok(value.clone())
+	}
+}
+
+impl FromValue for f32 {
+	fn from_value(value: &Value) -> Result<Self, Runtime> {
+		value.as_number()
+	}
+}
+
+impl FromValue for bool {
+	fn from_value(value: &Value) -> Result<Self, Runtime> {
+		value.as_bool()
+	}
+}
+
+impl FromValue for String {
+	fn from_value(value: &Value) -> Result<Self, Runtime> {
+		value.as_string()
+	}
+}
+
+impl FromValue for List {
+	fn from_value(value: &Value) -> Result<Self, Runtime> {
+		value.as_list()
+	}
+}
+
+impl<T: FromValue> FromValue for Option<T> {
+	fn from_value(value: &Value) -> Result<Self, Runtime> {
+		if *value == Value::null() {
+			return Ok(None);
+		}
+		T::from_value(value).map(Some)
+	}
+}
@@ -89,6 +89,59 @@ pub fn init() -> Result<(), String> {
 	Ok(())
 }
 
+/// Returns the `(version, build)` of the BYOND server we're running in, e.g. `(514, 1554)`.
+///
+/// Both are 0 until auxtools has been initialized.
 pub fn get() -> (u32, u32) {
 	unsafe { (BYOND_VERSION_MAJOR, BYOND_VERSION_MINOR) }
 }
+
+/// The version of the BYOND server we're running in.
+///
+/// BYOND versions are just a major version and a build, like `514.1554`. Builds keep counting up
+/// across major versions, so the build alone is enough to tell whether a feature exists.
+#[derive(Clone, Copy, Debug, PartialEq, Eq, PartialOrd, Ord, Hash)]
+pub struct ByondVersion {
+	pub major: u32,
+	pub build: u32,
+}
+
+impl std::fmt::Display for ByondVersion {
+	fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+		write!(f, "{}.{}", self.major, self.build)
+	}
+}
+
+/// Returns the version of the BYOND server we're running in.
+///
+/// This is read before any signatures are scanned for, so it's usable as soon as an init function
+/// runs. Both parts are 0 until auxtools has been initialized.
+pub fn byond_version() -> ByondVersion {
+	let (major, build) = get();
+	ByondVersion { major, build }
+}
+
+/// Returns an error if the BYOND server is older than `min_build`, for things that depend on
+/// features BYOND added later.
+///
+/// # Examples
+///
+/// ```ignore
+/// #[init(full)]
+/// fn init() -> Result<(), String> {
+///     require_version(1590).map_err(|e| e.message)?;
+///     Ok(())
+/// }
+/// ```
+pub fn require_version(min_build: u32) -> DMResult<()> {
+	let version = byond_version();
+	if version.build < min_build {
+		return Err(runtime!(
+			"BYOND build {} or newer is required, but this server is running {}",
+			min_build,
+			version
+		));
+	}
+
+	Ok(())
+}
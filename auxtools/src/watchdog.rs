@@ -0,0 +1,142 @@
+// An opt-in watchdog that notices proc hooks taking too long.
+//
+// BYOND runs everything on one thread, so a hook stuck in a loop hangs the whole server. We can't
+// interrupt Rust code, but a second thread can at least say which hook is stuck.
+use once_cell::sync::Lazy;
+use std::cell::Cell;
+use std::sync::atomic::{AtomicBool, Ordering};
+use std::sync::{Condvar, Mutex};
+use std::thread::{self, JoinHandle};
+use std::time::{Duration, Instant};
+
+struct RunningHook {
+	path: String,
+	started: Instant,
+	reported: bool,
+}
+
+struct State {
+	budget: Option<Duration>,
+	running: Option<RunningHook>,
+}
+
+static ENABLED: AtomicBool = AtomicBool::new(false);
+static STATE: Lazy<Mutex<State>> = Lazy::new(|| {
+	Mutex::new(State {
+		budget: None,
+		running: None,
+	})
+});
+// Wakes the watchdog thread up early when it's being stopped
+static WAKE: Lazy<Condvar> = Lazy::new(Condvar::new);
+static THREAD: Lazy<Mutex<Option<JoinHandle<()>>>> = Lazy::new(|| Mutex::new(None));
+
+thread_local! {
+	// How many hooks deep we are, so only the outermost one is timed
+	static DEPTH: Cell<u32> = Cell::new(0);
+}
+
+/// Starts logging a warning whenever a proc hook has been running for longer than `budget`, or
+/// turns that off again if `budget` is `None`.
+///
+/// The check happens on a separate thread, so a hook that never returns is still reported while it
+/// hangs. That's all the watchdog can do: Rust code can't be interrupted from outside, so the hook
+/// keeps running until it returns by itself. Only the outermost hook is timed, which means the time
+/// spent in any DM or hooks it calls counts towards its budget.
+///
+/// Warnings go through the `log` feature, so without it the watchdog has nothing to report to.
+pub fn set_hook_watchdog(budget: Option<Duration>) {
+	stop();
+
+	let budget = match budget {
+		Some(budget) => budget,
+		None => return,
+	};
+
+	STATE.lock().unwrap().budget = Some(budget);
+	ENABLED.store(true, Ordering::Relaxed);
+	*THREAD.lock().unwrap() = Some(thread::spawn(move || watch(budget)));
+}
+
+pub(crate) fn stop() {
+	ENABLED.store(false, Ordering::Relaxed);
+	STATE.lock().unwrap().budget = None;
+	WAKE.notify_all();
+
+	// Wait for the thread, as it can't be left running once the DLL is unloaded
+	if let Some(thread) = THREAD.lock().unwrap().take() {
+		let _ = thread.join();
+	}
+}
+
+fn watch(budget: Duration) {
+	// Check often enough that an overrun is noticed well within the budget
+	let interval = (budget / 4).min(Duration::from_millis(100));
+
+	let mut state = STATE.lock().unwrap();
+	while state.budget.is_some() {
+		state = WAKE.wait_timeout(state, interval).unwrap().0;
+
+		if let Some(running) = state.running.as_mut() {
+			let elapsed = running.started.elapsed();
+			if !running.reported && elapsed > budget {
+				running.reported = true;
+				aux_log!(
+					warn,
+					"hook for {} has been running for {:?}, over its {:?} budget",
+					running.path,
+					elapsed,
+					budget
+				);
+			}
+		}
+	}
+}
+
+/// Times a hook until the returned guard is dropped.
+pub(crate) fn watch_hook(path: &str) -> Option<HookTimer> {
+	if !ENABLED.load(Ordering::Relaxed) {
+		return None;
+	}
+
+	let outermost = DEPTH.with(|depth| {
+		depth.set(depth.get() + 1);
+		depth.get() == 1
+	});
+
+	if outermost {
+		STATE.lock().unwrap().running = Some(RunningHook {
+			path: path.to_owned(),
+			started: Instant::now(),
+			reported: false,
+		});
+	}
+
+	Some(HookTimer { outermost })
+}
+
+pub(crate) struct HookTimer {
+	outermost: bool,
+}
+
+impl Drop for HookTimer {
+	fn drop(&mut self) {
+		DEPTH.with(|depth| depth.set(depth.get() - 1));
+		if !self.outermost {
+			return;
+		}
+
+		let mut state = STATE.lock().unwrap();
+		if let Some(running) = state.running.take() {
+			// Hooks that overran but came back eventually are worth knowing about too
+			if running.reported {
+				aux_log!(
+					warn,
+					"hook for {} finished after {:?}",
+					running.path,
+					running.started.elapsed()
+				);
+			}
+		}
+	}
+}
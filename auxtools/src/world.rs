@@ -0,0 +1,60 @@
+//! Typed access to the map's dimensions and turfs.
+use crate::*;
+
+/// Equivalent to DM's `world.maxx`.
+pub fn max_x() -> DMResult<u32> {
+	Ok(Value::world().get_number(byond_string!("maxx"))? as u32)
+}
+
+/// Equivalent to DM's `world.maxy`.
+pub fn max_y() -> DMResult<u32> {
+	Ok(Value::world().get_number(byond_string!("maxy"))? as u32)
+}
+
+/// Equivalent to DM's `world.maxz`.
+pub fn max_z() -> DMResult<u32> {
+	Ok(Value::world().get_number(byond_string!("maxz"))? as u32)
+}
+
+/// Equivalent to DM's `locate(x, y, z)`, but returns `None` for coordinates outside of the map.
+///
+/// Coordinates are 1-based like they are in DM, so `turf_at(0, 1, 1)` is always `None`.
+pub fn turf_at(x: u32, y: u32, z: u32) -> DMResult<Option<Value>> {
+	let (max_x, max_y, max_z) = (max_x()?, max_y()?, max_z()?);
+	if !(1..=max_x).contains(&x) || !(1..=max_y).contains(&y) || !(1..=max_z).contains(&z) {
+		return Ok(None);
+	}
+
+	let (x, y, z) = (x - 1, y - 1, z - 1);
+	Ok(Some(unsafe {
+		Value::turf_by_id_unchecked(x + y * max_x + z * max_x * max_y)
+	}))
+}
+
+/// A position on the map. Like in DM, coordinates are 1-based.
+#[derive(Clone, Copy, PartialEq, Eq, Hash, Debug)]
+pub struct Coord {
+	pub x: u32,
+	pub y: u32,
+	pub z: u32,
+}
+
+impl Coord {
+	pub fn new(x: u32, y: u32, z: u32) -> Self {
+		Self { x, y, z }
+	}
+
+	/// Reads the `x`, `y` and `z` vars of an atom. Atoms that aren't on the map are at 0, 0, 0.
+	pub fn of(atom: &Value) -> DMResult<Self> {
+		Ok(Self {
+			x: atom.get_number(byond_string!("x"))? as u32,
+			y: atom.get_number(byond_string!("y"))? as u32,
+			z: atom.get_number(byond_string!("z"))? as u32,
+		})
+	}
+
+	/// The turf here, see [turf_at].
+	pub fn turf(&self) -> DMResult<Option<Value>> {
+		turf_at(self.x, self.y, self.z)
+	}
+}
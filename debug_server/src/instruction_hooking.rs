@@ -6,7 +6,7 @@ use crate::{
 use auxtools::*;
 use instruction_hooking::{
 	disassemble_env::{self, DisassembleEnv},
-	InstructionHook,
+	HookControl, InstructionHook,
 };
 use lazy_static::lazy_static;
 use std::cell::UnsafeCell;
@@ -208,15 +208,9 @@ fn handle_runtime(error: &str) {
 }
 
 impl InstructionHook for Server {
-	fn handle_instruction(&mut self, ctx: *mut raw_types::procs::ExecutionContext) {
+	fn handle_instruction(&mut self, ctx: *mut raw_types::procs::ExecutionContext) -> HookControl {
 		// Always handle the deferred instruction replacement first - everything else will depend on it
-		unsafe {
-			let deferred = DEFERRED_INSTRUCTION_REPLACE.get();
-			if let Some((src, dst)) = &*deferred {
-				std::ptr::copy_nonoverlapping(src.as_ptr(), *dst, src.len());
-				*deferred = None;
-			}
-		}
+		apply_deferred_replace();
 
 		let server = self;
 		if server.process() {
@@ -324,20 +318,66 @@ impl InstructionHook for Server {
 				}
 			}
 
-			// ORIGINAL_BYTECODE won't contain an entry if this breakpoint has already been removed
-			let map = ORIGINAL_BYTECODE.lock().unwrap();
-			if let Some(original) = map.get(&PtrKey::new(opcode_ptr)) {
-				unsafe {
-					let deferred_replace = DEFERRED_INSTRUCTION_REPLACE.get();
-					assert_eq!(*deferred_replace, None);
-					*deferred_replace = Some((
-						std::slice::from_raw_parts(opcode_ptr, original.len()).to_vec(),
-						opcode_ptr,
-					));
-					std::ptr::copy_nonoverlapping(original.as_ptr(), opcode_ptr, original.len());
-				}
+			restore_breakpoint(opcode_ptr);
+		}
+
+		HookControl::Keep
+	}
+}
+
+// Puts a breakpoint back once the instruction it stood in for has been executed
+fn apply_deferred_replace() {
+	unsafe {
+		let deferred = DEFERRED_INSTRUCTION_REPLACE.get();
+		if let Some((src, dst)) = &*deferred {
+			std::ptr::copy_nonoverlapping(src.as_ptr(), *dst, src.len());
+			*deferred = None;
+		}
+	}
+}
+
+// Swaps a breakpoint for the instruction it replaced, so BYOND executes that instead
+fn restore_breakpoint(opcode_ptr: *mut u32) {
+	// A breakpoint hit from inside the debugger may not have been put back yet
+	apply_deferred_replace();
+
+	// ORIGINAL_BYTECODE won't contain an entry if this breakpoint has already been removed
+	let map = ORIGINAL_BYTECODE.lock().unwrap();
+	if let Some(original) = map.get(&PtrKey::new(opcode_ptr)) {
+		unsafe {
+			*DEFERRED_INSTRUCTION_REPLACE.get() = Some((
+				std::slice::from_raw_parts(opcode_ptr, original.len()).to_vec(),
+				opcode_ptr,
+			));
+			std::ptr::copy_nonoverlapping(original.as_ptr(), opcode_ptr, original.len());
+		}
+	}
+}
+
+/// Set while the debug server's hook is running. Instructions run from inside it, like those of
+/// evaluated expressions, don't reach that hook, so [NestedBreakpointHook] handles them instead.
+pub static mut IN_SERVER_HOOK: bool = false;
+
+/// Lets code the debugger runs itself pass over breakpoints without pausing, the way runtimes in
+/// eval code aren't caught either.
+pub struct NestedBreakpointHook;
+
+impl InstructionHook for NestedBreakpointHook {
+	fn handle_instruction(&mut self, ctx: *mut raw_types::procs::ExecutionContext) -> HookControl {
+		unsafe {
+			if !IN_SERVER_HOOK {
+				return HookControl::Keep;
+			}
+
+			apply_deferred_replace();
+
+			let opcode_ptr = (*ctx).bytecode.add((*ctx).bytecode_offset as usize);
+			if *opcode_ptr == OPCODE_DEBUG_BREAK {
+				restore_breakpoint(opcode_ptr);
 			}
 		}
+
+		HookControl::Keep
 	}
 }
 
@@ -14,7 +14,7 @@ mod mem_profiler;
 #[cfg(not(windows))]
 mod mem_profiler_stub;
 
-use ::instruction_hooking::{InstructionHook, INSTRUCTION_HOOKS};
+use ::instruction_hooking::{HookControl, InstructionHook, INSTRUCTION_HOOKS};
 #[cfg(not(windows))]
 use mem_profiler_stub as mem_profiler;
 
@@ -56,10 +56,15 @@ struct DebugServerInstructionHook<'a> {
 }
 
 impl InstructionHook for DebugServerInstructionHook<'static> {
-	fn handle_instruction(&mut self, ctx: *mut raw_types::procs::ExecutionContext) {
+	fn handle_instruction(&mut self, ctx: *mut raw_types::procs::ExecutionContext) -> HookControl {
 		if let Some(debug_server) = self.debug_server.get_mut() {
-			debug_server.handle_instruction(ctx);
+			unsafe {
+				crate::instruction_hooking::IN_SERVER_HOOK = true;
+				debug_server.handle_instruction(ctx);
+				crate::instruction_hooking::IN_SERVER_HOOK = false;
+			}
 		}
+		HookControl::Keep
 	}
 }
 
@@ -103,9 +108,9 @@ fn enable_debugging(mode: Value, port: Value) {
 			debug_server: &mut DEBUG_SERVER,
 		};
 
-		INSTRUCTION_HOOKS
-			.get_mut()
-			.push(Box::new(debug_server_instruction_hook));
+		let hooks = INSTRUCTION_HOOKS.get_mut();
+		hooks.push(Box::new(debug_server_instruction_hook));
+		hooks.push(Box::new(crate::instruction_hooking::NestedBreakpointHook));
 	}
 
 	Ok(Value::null())
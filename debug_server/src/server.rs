@@ -300,15 +300,15 @@ impl Server {
 
 	fn stringify(value: &Value) -> String {
 		if List::is_list(value) {
-			match List::from_value(value) {
-				Ok(list) => format!("/list {{len = {}}}", list.len()),
-				Err(Runtime { message }) => format!("/list (failed to get len: {:?})", message),
+			match List::from_value(value).and_then(|list| list.len()) {
+				Ok(len) => format!("/list {{len = {}}}", len),
+				Err(Runtime { message, .. }) => format!("/list (failed to get len: {:?})", message),
 			}
 		} else {
 			match value.to_string() {
 				Ok(v) if v.is_empty() => value.raw.to_string(),
 				Ok(value) => value,
-				Err(Runtime { message }) => {
+				Err(Runtime { message, .. }) => {
 					format!("{} -- stringify error: {:?}", value.raw, message)
 				}
 			}
@@ -343,7 +343,7 @@ impl Server {
 	fn list_to_variables(&mut self, value: &Value) -> Result<Vec<Variable>, Runtime> {
 		let state = self.state.as_ref().unwrap();
 		let list = List::from_value(value)?;
-		let len = list.len();
+		let len = list.len()?;
 
 		let mut variables = vec![];
 
@@ -389,7 +389,7 @@ impl Server {
 		let mut variables = vec![];
 		let mut top_variables = vec![]; // These fields get displayed on top of all others
 
-		for i in 1..=vars.len() {
+		for i in 1..=vars.len()? {
 			let name = vars.get(i)?.as_string()?;
 			let value = value.get(StringRef::new(name.as_str())?)?;
 			let variable = self.value_to_variable(name, &value);
@@ -901,7 +901,7 @@ impl Server {
 				if let Ok(list) = res.as_list() {
 					// The rest are the potentially mutated parameters. We need to commit them to the function that called us.
 					// TODO: This sucks, obviously.
-					let len = list.len();
+					let len = list.len().unwrap_or(0);
 					for i in 2..=len {
 						let value = list.get(i).unwrap();
 						let slot = &args[i as usize - 2].2;
@@ -0,0 +1,73 @@
+//! Breakpoints on a proc's bytecode offset, checked before every instruction.
+//!
+//! When nothing is set this costs an emptiness check per instruction, and otherwise a single hash lookup.
+use super::{HookControl, InstructionHook, INSTRUCTION_HOOKS};
+use auxtools::debug::ExecutionContextRef;
+use auxtools::raw_types::procs::{ExecutionContext, ProcId};
+use auxtools::*;
+use std::cell::{Cell, RefCell};
+use std::collections::HashSet;
+
+/// Called when execution reaches a breakpoint, before the instruction there runs.
+pub type BreakpointHandler = fn(&mut ExecutionContextRef);
+
+thread_local! {
+	static BREAKPOINTS: RefCell<HashSet<(ProcId, u16)>> = RefCell::new(HashSet::new());
+	static HANDLER: Cell<Option<BreakpointHandler>> = Cell::new(None);
+}
+
+/// Sets the function called whenever a breakpoint is hit, replacing any previous one.
+pub fn set_handler(handler: BreakpointHandler) {
+	HANDLER.with(|h| h.set(Some(handler)));
+}
+
+/// Breaks before the instruction at `offset` in `proc`'s bytecode. Returns false if there was
+/// already a breakpoint there.
+pub fn set_breakpoint(proc: ProcId, offset: u16) -> bool {
+	BREAKPOINTS.with(|b| b.borrow_mut().insert((proc, offset)))
+}
+
+/// Returns false if there wasn't a breakpoint there.
+pub fn clear_breakpoint(proc: ProcId, offset: u16) -> bool {
+	BREAKPOINTS.with(|b| b.borrow_mut().remove(&(proc, offset)))
+}
+
+pub fn clear_all() {
+	BREAKPOINTS.with(|b| b.borrow_mut().clear());
+}
+
+struct BreakpointHook;
+
+impl InstructionHook for BreakpointHook {
+	fn handle_instruction(&mut self, ctx: *mut ExecutionContext) -> HookControl {
+		let mut ctx = unsafe { ExecutionContextRef::from_raw(ctx) };
+
+		let hit = BREAKPOINTS.with(|b| {
+			let breakpoints = b.borrow();
+			!breakpoints.is_empty() && breakpoints.contains(&(ctx.proc_id(), ctx.bytecode_offset()))
+		});
+
+		// The set isn't borrowed here, so the handler is free to add or remove breakpoints
+		if hit {
+			if let Some(handler) = HANDLER.with(|h| h.get()) {
+				handler(&mut ctx);
+			}
+		}
+
+		HookControl::Keep
+	}
+}
+
+#[init(partial)]
+fn breakpoints_init() -> Result<(), String> {
+	unsafe {
+		INSTRUCTION_HOOKS.get_mut().push(Box::new(BreakpointHook));
+	}
+	Ok(())
+}
+
+#[shutdown]
+fn breakpoints_shutdown() {
+	clear_all();
+	HANDLER.with(|h| h.set(None));
+}
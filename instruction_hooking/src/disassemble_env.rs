@@ -1,5 +1,6 @@
 use auxtools::*;
 use dmasm;
+use std::convert::TryFrom;
 
 pub struct DisassembleEnv;
 
@@ -29,12 +30,11 @@ impl dmasm::disassembler::DisassembleEnv for DisassembleEnv {
 	}
 
 	fn value_to_string_data(&mut self, tag: u32, data: u32) -> Option<Vec<u8>> {
-		unsafe {
-			let value = Value::new(std::mem::transmute(tag as u8), std::mem::transmute(data));
-			match value.to_dmstring() {
-				Ok(s) => Some(s.data().to_vec()),
-				_ => None,
-			}
+		let tag = raw_types::values::ValueTag::try_from(tag as u8).ok()?;
+		let value = unsafe { Value::new(tag, raw_types::values::ValueData { id: data }) };
+		match value.to_dmstring() {
+			Ok(s) => Some(s.data().to_vec()),
+			_ => None,
 		}
 	}
 }
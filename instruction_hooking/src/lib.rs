@@ -1,6 +1,16 @@
+pub mod breakpoints;
 pub mod disassemble_env;
+pub mod opcode_histogram;
+pub mod sampling_profiler;
+pub mod timer;
 
-use std::{any::Any, cell::UnsafeCell, ffi::c_void};
+use std::{
+	any::Any,
+	cell::{Cell, RefCell, UnsafeCell},
+	collections::HashMap,
+	ffi::c_void,
+	sync::atomic::{AtomicBool, Ordering},
+};
 
 use auxtools::*;
 use detour::RawDetour;
@@ -29,13 +39,136 @@ impl<T: 'static> InstructionHookToAny for T {
 	}
 }
 
+/// Returned by [InstructionHook::handle_instruction] to say whether the hook should keep running.
+#[derive(Clone, Copy, PartialEq, Eq, Debug)]
+pub enum HookControl {
+	Keep,
+	/// Removes the hook once every hook has handled the current instruction.
+	Remove,
+}
+
+/// Called before BYOND executes each instruction.
+///
+/// Instructions BYOND executes on a hook's behalf, e.g. in a proc the hook calls, run every hook
+/// except the ones still running, which aren't reentered. Hooks that must see those instructions too,
+/// like the debug server's, need a second hook to handle them.
+///
+/// Hooks may move `ctx`'s bytecode offset (see `auxtools::debug::ExecutionContextRef::set_bytecode_offset`)
+/// to make BYOND execute a different instruction instead.
 pub trait InstructionHook: InstructionHookToAny {
-	fn handle_instruction(&mut self, ctx: *mut raw_types::procs::ExecutionContext);
+	fn handle_instruction(&mut self, ctx: *mut raw_types::procs::ExecutionContext) -> HookControl;
 }
 
 pub static mut INSTRUCTION_HOOKS: UnsafeCell<Vec<Box<dyn InstructionHook>>> =
 	UnsafeCell::new(Vec::new());
 
+thread_local! {
+	// How many instructions are being dispatched, counting ones run from inside a hook
+	static DEPTH: Cell<u32> = Cell::new(0);
+
+	// Set once a hook asks to be removed, so the hook lists get purged when dispatch is done
+	static PURGE: Cell<bool> = Cell::new(false);
+
+	// See [HookChange]
+	static PENDING_CHANGES: RefCell<Vec<HookChange>> = RefCell::new(Vec::new());
+
+	// Hooks that only run for instructions in a single proc
+	static PROC_INSTRUCTION_HOOKS: UnsafeCell<HashMap<raw_types::procs::ProcId, Vec<Box<dyn InstructionHook>>>> =
+		UnsafeCell::new(HashMap::new());
+
+	// Hooks that only run for a single opcode, indexed by that opcode
+	static OPCODE_INSTRUCTION_HOOKS: UnsafeCell<Vec<Vec<Box<dyn InstructionHook>>>> =
+		UnsafeCell::new(Vec::new());
+}
+
+/// Adds a hook that only runs before instructions in the proc `proc_id`. Instructions in other procs
+/// cost a single map lookup however many of these there are.
+///
+/// This can be called from inside an instruction hook, or code one runs. The hook is added once the
+/// current instruction has been dispatched.
+pub fn add_instruction_hook_for_proc(
+	proc_id: raw_types::procs::ProcId,
+	hook: Box<dyn InstructionHook>,
+) {
+	change_hooks(HookChange::AddForProc(proc_id, hook));
+}
+
+/// Removes every hook added to `proc_id` with [add_instruction_hook_for_proc]. Like adding them, this
+/// waits until the current instruction has been dispatched if it's called from inside a hook.
+pub fn remove_instruction_hooks_for_proc(proc_id: raw_types::procs::ProcId) {
+	change_hooks(HookChange::RemoveForProc(proc_id));
+}
+
+/// Adds a hook that only runs before instructions with the given opcode, e.g. to trace every call
+/// without paying for every other instruction. Opcodes are the raw values found in bytecode.
+///
+/// As with [add_instruction_hook_for_proc], calling this from inside a hook adds the hook once the
+/// current instruction has been dispatched.
+pub fn add_instruction_hook_for_opcode(opcode: u32, hook: Box<dyn InstructionHook>) {
+	change_hooks(HookChange::AddForOpcode(opcode, hook));
+}
+
+/// Removes every hook added to `opcode` with [add_instruction_hook_for_opcode]. Calling this from
+/// inside a hook waits the same way.
+pub fn remove_instruction_hooks_for_opcode(opcode: u32) {
+	change_hooks(HookChange::RemoveForOpcode(opcode));
+}
+
+// A change to the per-proc or per-opcode hooks. Ones made while instructions are being dispatched are queued until
+// dispatch is done, so the lists never change under a running hook.
+enum HookChange {
+	AddForProc(raw_types::procs::ProcId, Box<dyn InstructionHook>),
+	RemoveForProc(raw_types::procs::ProcId),
+	AddForOpcode(u32, Box<dyn InstructionHook>),
+	RemoveForOpcode(u32),
+}
+
+fn change_hooks(change: HookChange) {
+	if DEPTH.with(|depth| depth.get()) > 0 {
+		PENDING_CHANGES.with(|changes| changes.borrow_mut().push(change));
+		return;
+	}
+
+	match change {
+		HookChange::AddForProc(proc_id, hook) => {
+			PROC_INSTRUCTION_HOOKS.with(|hooks| unsafe {
+				(*hooks.get()).entry(proc_id).or_default().push(hook);
+			});
+		}
+		HookChange::RemoveForProc(proc_id) => {
+			PROC_INSTRUCTION_HOOKS.with(|hooks| unsafe {
+				(*hooks.get()).remove(&proc_id);
+			});
+		}
+		HookChange::AddForOpcode(opcode, hook) => {
+			OPCODE_INSTRUCTION_HOOKS.with(|hooks| unsafe {
+				let hooks = &mut *hooks.get();
+				let index = opcode as usize;
+				if hooks.len() <= index {
+					hooks.resize_with(index + 1, Vec::new);
+				}
+				hooks[index].push(hook);
+			});
+		}
+		HookChange::RemoveForOpcode(opcode) => {
+			OPCODE_INSTRUCTION_HOOKS.with(|hooks| unsafe {
+				let hooks = &mut *hooks.get();
+				if let Some(opcode_hooks) = hooks.get_mut(opcode as usize) {
+					opcode_hooks.clear();
+				}
+				trim_opcode_hooks(hooks);
+			});
+		}
+	}
+}
+
+// Drops empty slots off the end, so no opcode hooks at all means not even reading the opcode
+fn trim_opcode_hooks(hooks: &mut Vec<Vec<Box<dyn InstructionHook>>>) {
+	while hooks.last().map_or(false, |last| last.is_empty()) {
+		hooks.pop();
+	}
+}
+
 extern "C" {
 	// Trampoline to the original un-hooked BYOND execute_instruction code
 	static mut execute_instruction_original: *const c_void;
@@ -44,23 +177,59 @@ extern "C" {
 	fn execute_instruction_hook();
 }
 
+static AVAILABLE: AtomicBool = AtomicBool::new(false);
+
+/// Returns false if the execute_instruction detour couldn't be installed, in which case instruction
+/// hooks never run.
+///
+/// Without the `optional` feature that failure stops auxtools from loading instead, so this is only
+/// worth checking when the feature is enabled.
+pub fn is_available() -> bool {
+	AVAILABLE.load(Ordering::Relaxed)
+}
+
 #[init(full)]
 fn instruction_hooking_init() -> Result<(), String> {
-	let byondcore = sigscan::Scanner::for_module(BYONDCORE).unwrap();
-
-	find_signatures_result! { byondcore,
-		execute_instruction
+	match install_detour() {
+		Ok(()) => {
+			AVAILABLE.store(true, Ordering::Relaxed);
+			Ok(())
+		}
+		// Let everything else in auxtools carry on without us
+		Err(_) if cfg!(feature = "optional") => Ok(()),
+		Err(e) => Err(e.to_string()),
 	}
+}
+
+fn install_detour() -> Result<(), DetourFailure> {
+	let byondcore = sigscan::Scanner::for_module(BYONDCORE)
+		.ok_or_else(|| DetourFailure::new("execute_instruction", "couldn't find byondcore"))?;
+
+	let (major, build) = version::get();
+	let execute_instruction = SIGNATURES0
+		.execute_instruction
+		.find(&byondcore, build)
+		.ok_or_else(|| {
+			DetourFailure::new(
+				"execute_instruction",
+				format!(
+					"couldn't find it in {} for BYOND {}.{}",
+					byondcore.module_name(),
+					major,
+					build
+				),
+			)
+		})?;
 
 	unsafe {
 		let hook = RawDetour::new(
 			execute_instruction as *const (),
 			execute_instruction_hook as *const (),
 		)
-		.map_err(|_| "Couldn't detour execute_instruction")?;
+		.map_err(|e| DetourFailure::new("execute_instruction", e))?;
 
 		hook.enable()
-			.map_err(|_| "Couldn't enable execute_instruction detour")?;
+			.map_err(|e| DetourFailure::new("execute_instruction", e))?;
 
 		execute_instruction_original = std::mem::transmute(hook.trampoline());
 
@@ -76,6 +245,27 @@ fn instruction_hooking_shutdown() {
 	unsafe {
 		INSTRUCTION_HOOKS.get_mut().clear();
 	}
+	PENDING_CHANGES.with(|changes| changes.borrow_mut().clear());
+	PROC_INSTRUCTION_HOOKS.with(|hooks| unsafe { (*hooks.get()).clear() });
+	OPCODE_INSTRUCTION_HOOKS.with(|hooks| unsafe { (*hooks.get()).clear() });
+}
+
+// Sits in a hook's slot while it runs, so instructions the hook causes don't reenter it
+struct Running;
+
+// Sits in the slot of a hook that asked to be removed until nothing is being dispatched
+struct Removed;
+
+impl InstructionHook for Running {
+	fn handle_instruction(&mut self, _: *mut raw_types::procs::ExecutionContext) -> HookControl {
+		HookControl::Keep
+	}
+}
+
+impl InstructionHook for Removed {
+	fn handle_instruction(&mut self, _: *mut raw_types::procs::ExecutionContext) -> HookControl {
+		HookControl::Keep
+	}
 }
 
 // Handles any instruction BYOND tries to execute.
@@ -84,11 +274,110 @@ fn instruction_hooking_shutdown() {
 extern "C" fn handle_instruction(
 	ctx: *mut raw_types::procs::ExecutionContext,
 ) -> *const raw_types::procs::ExecutionContext {
+	let depth = DEPTH.with(|d| d.replace(d.get() + 1));
+
 	unsafe {
-		for vec_box in &mut *INSTRUCTION_HOOKS.get() {
-			vec_box.handle_instruction(ctx);
+		run_hooks(|| Some(INSTRUCTION_HOOKS.get()), ctx);
+
+		if PROC_INSTRUCTION_HOOKS.with(|hooks| !(*hooks.get()).is_empty()) {
+			let proc_id = (*(*ctx).proc_instance).proc;
+			run_hooks(
+				|| {
+					PROC_INSTRUCTION_HOOKS.with(|hooks| {
+						(*hooks.get())
+							.get_mut(&proc_id)
+							.map(|proc_hooks| proc_hooks as *mut _)
+					})
+				},
+				ctx,
+			);
+		}
+
+		if OPCODE_INSTRUCTION_HOOKS.with(|hooks| !(*hooks.get()).is_empty()) {
+			let opcode = *(*ctx).bytecode.add((*ctx).bytecode_offset as usize) as usize;
+			run_hooks(
+				|| {
+					OPCODE_INSTRUCTION_HOOKS.with(|hooks| {
+						(&mut *hooks.get())
+							.get_mut(opcode)
+							.map(|opcode_hooks| opcode_hooks as *mut _)
+					})
+				},
+				ctx,
+			);
+		}
+	}
+
+	// Callbacks only run once no hook is running, so their instructions reach every hook
+	if depth == 0 {
+		timer::run_due();
+	}
+
+	DEPTH.with(|d| d.set(depth));
+	if depth == 0 {
+		if PURGE.with(|purge| purge.replace(false)) {
+			purge_removed();
 		}
+
+		let changes = PENDING_CHANGES.with(|changes| std::mem::take(&mut *changes.borrow_mut()));
+		changes.into_iter().for_each(change_hooks);
 	}
 
 	ctx
 }
+
+// Runs each hook in the list `hooks` finds. We don't hold on to the list while a hook runs, as the
+// hook might run DM code whose instructions come back through here. Instead each hook is swapped out
+// for a [Running] while it runs and the list is looked up again to put it back.
+unsafe fn run_hooks(
+	hooks: impl Fn() -> Option<*mut Vec<Box<dyn InstructionHook>>>,
+	ctx: *mut raw_types::procs::ExecutionContext,
+) {
+	let mut index = 0;
+
+	loop {
+		let mut hook = match hooks().map(|list| &mut *list) {
+			Some(list) if index < list.len() => {
+				std::mem::replace(&mut list[index], Box::new(Running))
+			}
+			_ => return,
+		};
+
+		let control = hook.handle_instruction(ctx);
+
+		if let Some(slot) = hooks().and_then(|list| (&mut *list).get_mut(index)) {
+			*slot = match control {
+				HookControl::Keep => hook,
+				HookControl::Remove => {
+					PURGE.with(|purge| purge.set(true));
+					Box::new(Removed)
+				}
+			};
+		}
+
+		index += 1;
+	}
+}
+
+fn purge_removed() {
+	fn retain_hooks(hooks: &mut Vec<Box<dyn InstructionHook>>) {
+		hooks.retain_mut(|hook| !(**hook).as_any().is::<Removed>());
+	}
+
+	unsafe {
+		retain_hooks(&mut *INSTRUCTION_HOOKS.get());
+	}
+
+	PROC_INSTRUCTION_HOOKS.with(|hooks| unsafe {
+		(*hooks.get()).retain(|_, proc_hooks| {
+			retain_hooks(proc_hooks);
+			!proc_hooks.is_empty()
+		});
+	});
+
+	OPCODE_INSTRUCTION_HOOKS.with(|hooks| unsafe {
+		let hooks = &mut *hooks.get();
+		hooks.iter_mut().for_each(retain_hooks);
+		trim_opcode_hooks(hooks);
+	});
+}
@@ -0,0 +1,77 @@
+//! Counts how many times each opcode is executed, as a cheap instruction-level profiler.
+//!
+//! Opcodes are reported as the raw values found in bytecode. `dmasm` can be used to put names to them.
+use super::{HookControl, InstructionHook, INSTRUCTION_HOOKS};
+use auxtools::raw_types::procs::ExecutionContext;
+use auxtools::*;
+use std::sync::atomic::{AtomicBool, AtomicU64, Ordering};
+
+// Comfortably more opcodes than BYOND has. Anything past this isn't counted.
+const MAX_OPCODES: usize = 0x1000;
+
+#[allow(clippy::declare_interior_mutable_const)]
+const ZERO: AtomicU64 = AtomicU64::new(0);
+static COUNTS: [AtomicU64; MAX_OPCODES] = [ZERO; MAX_OPCODES];
+static RUNNING: AtomicBool = AtomicBool::new(false);
+
+/// Throws away any previous counts and starts counting.
+pub fn start() {
+	for count in COUNTS.iter() {
+		count.store(0, Ordering::Relaxed);
+	}
+	RUNNING.store(true, Ordering::Relaxed);
+}
+
+/// Stops counting. The counts so far are kept for [report].
+pub fn stop() {
+	RUNNING.store(false, Ordering::Relaxed);
+}
+
+pub fn is_running() -> bool {
+	RUNNING.load(Ordering::Relaxed)
+}
+
+/// Returns every opcode executed at least once along with how many times, most frequent first.
+pub fn report() -> Vec<(u32, u64)> {
+	let mut report: Vec<(u32, u64)> = COUNTS
+		.iter()
+		.enumerate()
+		.map(|(opcode, count)| (opcode as u32, count.load(Ordering::Relaxed)))
+		.filter(|(_, count)| *count != 0)
+		.collect();
+
+	report.sort_by(|a, b| b.1.cmp(&a.1));
+	report
+}
+
+struct OpcodeHistogramHook;
+
+impl InstructionHook for OpcodeHistogramHook {
+	fn handle_instruction(&mut self, ctx: *mut ExecutionContext) -> HookControl {
+		if !RUNNING.load(Ordering::Relaxed) {
+			return HookControl::Keep;
+		}
+
+		let opcode = unsafe { *(*ctx).bytecode.add((*ctx).bytecode_offset as usize) } as usize;
+		if let Some(count) = COUNTS.get(opcode) {
+			count.fetch_add(1, Ordering::Relaxed);
+		}
+
+		HookControl::Keep
+	}
+}
+
+#[init(partial)]
+fn opcode_histogram_init() -> Result<(), String> {
+	unsafe {
+		INSTRUCTION_HOOKS
+			.get_mut()
+			.push(Box::new(OpcodeHistogramHook));
+	}
+	Ok(())
+}
+
+#[shutdown]
+fn opcode_histogram_shutdown() {
+	stop();
+}
@@ -0,0 +1,85 @@
+//! A statistical profiler that records which proc is running every Nth instruction.
+//!
+//! Cheap enough to leave running on a live server, where [opcode_histogram](super::opcode_histogram)
+//! or the proc-level `auxtools::profiler` would cost too much. Procs that run for longer get sampled more.
+use super::{HookControl, InstructionHook, INSTRUCTION_HOOKS};
+use auxtools::raw_types::procs::{ExecutionContext, ProcId};
+use auxtools::*;
+use std::cell::RefCell;
+use std::collections::HashMap;
+use std::sync::atomic::{AtomicU32, Ordering};
+
+// 0 while stopped
+static SAMPLE_RATE: AtomicU32 = AtomicU32::new(0);
+static UNTIL_NEXT_SAMPLE: AtomicU32 = AtomicU32::new(0);
+
+thread_local! {
+	static SAMPLES: RefCell<HashMap<ProcId, u64>> = RefCell::new(HashMap::new());
+}
+
+/// Throws away any previous samples and starts sampling once every `sample_rate` instructions.
+pub fn start(sample_rate: u32) {
+	SAMPLES.with(|s| s.borrow_mut().clear());
+	UNTIL_NEXT_SAMPLE.store(sample_rate, Ordering::Relaxed);
+	SAMPLE_RATE.store(sample_rate, Ordering::Relaxed);
+}
+
+/// Stops sampling. The samples so far are kept for [report].
+pub fn stop() {
+	SAMPLE_RATE.store(0, Ordering::Relaxed);
+}
+
+pub fn is_running() -> bool {
+	SAMPLE_RATE.load(Ordering::Relaxed) != 0
+}
+
+/// Returns how many samples landed in each proc, most sampled first.
+pub fn report() -> Vec<(Proc, u64)> {
+	let mut report: Vec<(Proc, u64)> = SAMPLES.with(|s| {
+		s.borrow()
+			.iter()
+			.filter_map(|(id, samples)| Some((Proc::from_id(*id)?, *samples)))
+			.collect()
+	});
+
+	report.sort_by(|a, b| b.1.cmp(&a.1));
+	report
+}
+
+struct SamplingHook;
+
+impl InstructionHook for SamplingHook {
+	fn handle_instruction(&mut self, ctx: *mut ExecutionContext) -> HookControl {
+		// The common case: a decrement and a branch
+		let until_next = UNTIL_NEXT_SAMPLE.load(Ordering::Relaxed);
+		if until_next > 1 {
+			UNTIL_NEXT_SAMPLE.store(until_next - 1, Ordering::Relaxed);
+			return HookControl::Keep;
+		}
+
+		let sample_rate = SAMPLE_RATE.load(Ordering::Relaxed);
+		// Stopped, which leaves the countdown at 1
+		if sample_rate == 0 {
+			return HookControl::Keep;
+		}
+		UNTIL_NEXT_SAMPLE.store(sample_rate, Ordering::Relaxed);
+
+		let proc_id = unsafe { (*(*ctx).proc_instance).proc };
+		SAMPLES.with(|s| *s.borrow_mut().entry(proc_id).or_insert(0) += 1);
+
+		HookControl::Keep
+	}
+}
+
+#[init(partial)]
+fn sampling_profiler_init() -> Result<(), String> {
+	unsafe {
+		INSTRUCTION_HOOKS.get_mut().push(Box::new(SamplingHook));
+	}
+	Ok(())
+}
+
+#[shutdown]
+fn sampling_profiler_shutdown() {
+	stop();
+}
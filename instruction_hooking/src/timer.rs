@@ -0,0 +1,146 @@
+//! Runs closures on BYOND's main thread after a number of ticks have passed.
+//!
+//! Callbacks are run just before BYOND executes an instruction, after every instruction hook has
+//! handled it, so they're as safe to touch [Values](auxtools::Value) from as any proc hook. They won't
+//! run while no DM code is running, and a due callback may wait a few hundred instructions while
+//! `world.time` is rechecked. That's usually still the same tick, so a callback scheduled for 0 ticks
+//! from now runs "soon" rather than on the next tick. To call a proc later from DM's scheduler instead,
+//! see [Proc::spawn].
+use auxtools::*;
+use std::cell::{Cell, RefCell};
+use std::cmp::Ordering;
+use std::collections::BinaryHeap;
+use std::panic;
+
+// Reading world.time isn't free, so only recheck it this often while something is scheduled
+const TIME_CHECK_INTERVAL: u32 = 256;
+
+struct Scheduled {
+	due: f32,
+	// Breaks ties between callbacks due at the same time, so they run in the order they were scheduled
+	sequence: u64,
+	callback: Box<dyn FnOnce()>,
+}
+
+impl PartialEq for Scheduled {
+	fn eq(&self, other: &Self) -> bool {
+		self.cmp(other) == Ordering::Equal
+	}
+}
+
+impl Eq for Scheduled {}
+
+impl PartialOrd for Scheduled {
+	fn partial_cmp(&self, other: &Self) -> Option<Ordering> {
+		Some(self.cmp(other))
+	}
+}
+
+// Reversed, so the BinaryHeap pops the earliest callback first
+impl Ord for Scheduled {
+	fn cmp(&self, other: &Self) -> Ordering {
+		other
+			.due
+			.partial_cmp(&self.due)
+			.unwrap_or(Ordering::Equal)
+			.then_with(|| other.sequence.cmp(&self.sequence))
+	}
+}
+
+thread_local! {
+	static QUEUE: RefCell<BinaryHeap<Scheduled>> = RefCell::new(BinaryHeap::new());
+	static NEXT_SEQUENCE: Cell<u64> = Cell::new(0);
+	static INSTRUCTIONS_UNTIL_CHECK: Cell<u32> = Cell::new(0);
+}
+
+/// Runs `callback` once at least `ticks` ticks have passed. Callbacks due on the same tick run in the
+/// order they were scheduled.
+///
+/// A callback that panics is stopped there, and the callbacks after it still run.
+///
+/// Must be called from BYOND's main thread, see [on_main_thread]. Fails if instruction hooking isn't
+/// [available](crate::is_available), as the callback would never run.
+pub fn schedule_after<F: FnOnce() + 'static>(ticks: u32, callback: F) -> DMResult<()> {
+	if !on_main_thread() {
+		return Err(runtime!(
+			"timer::schedule_after called off of BYOND's main thread"
+		));
+	}
+
+	if !crate::is_available() {
+		return Err(runtime!(
+			"timer::schedule_after called without instruction hooking available"
+		));
+	}
+
+	let world = Value::world();
+	let now = world.get_number(byond_string!("time"))?;
+	let tick_lag = world.get_number(byond_string!("tick_lag"))?;
+
+	let sequence = NEXT_SEQUENCE.with(|s| {
+		let sequence = s.get();
+		s.set(sequence + 1);
+		sequence
+	});
+
+	QUEUE.with(|q| {
+		q.borrow_mut().push(Scheduled {
+			due: now + ticks as f32 * tick_lag,
+			sequence,
+			callback: Box::new(callback),
+		})
+	});
+
+	Ok(())
+}
+
+// Pops the next callback if it's due. The queue isn't borrowed while the callback runs, so callbacks
+// can schedule more callbacks.
+fn pop_due(now: f32) -> Option<Box<dyn FnOnce()>> {
+	QUEUE.with(|q| {
+		let mut queue = q.borrow_mut();
+		if queue.peek()?.due > now {
+			return None;
+		}
+		queue.pop().map(|scheduled| scheduled.callback)
+	})
+}
+
+// Runs every due callback. Called by the instruction detour once the hooks for the current
+// instruction are done with, so callbacks are free to run DM code and add instruction hooks.
+pub(crate) fn run_due() {
+	if QUEUE.with(|q| q.borrow().is_empty()) {
+		return;
+	}
+
+	let should_check = INSTRUCTIONS_UNTIL_CHECK.with(|count| match count.get() {
+		0 => {
+			count.set(TIME_CHECK_INTERVAL);
+			true
+		}
+		n => {
+			count.set(n - 1);
+			false
+		}
+	});
+
+	if !should_check {
+		return;
+	}
+
+	let now = match Value::world().get_number(byond_string!("time")) {
+		Ok(now) => now,
+		Err(_) => return,
+	};
+
+	while let Some(callback) = pop_due(now) {
+		// We're called from BYOND, which a panic mustn't unwind into
+		let _ = panic::catch_unwind(panic::AssertUnwindSafe(callback));
+	}
+}
+
+#[shutdown]
+fn timer_shutdown() {
+	QUEUE.with(|q| q.borrow_mut().clear());
+	INSTRUCTIONS_UNTIL_CHECK.with(|count| count.set(0));
+}
@@ -0,0 +1,39 @@
+use auxtools::*;
+
+#[hook("/proc/auxtest_appearance")]
+fn test_appearance(obj: Value) {
+	let icon = Value::new_icon("auxtest.dmi")?;
+	obj.set(byond_string!("icon"), icon)?;
+	obj.set_icon_state("one")?;
+
+	if obj
+		.get_appearance_var(byond_string!("icon_state"))?
+		.as_string()?
+		!= "one"
+	{
+		return Err(runtime!(
+			"test_appearance: the appearance's icon_state wasn't updated"
+		));
+	}
+
+	if Value::new_icon("auxtest_missing.dmi").is_ok() {
+		return Err(runtime!(
+			"test_appearance: loaded an icon that doesn't exist"
+		));
+	}
+
+	obj.set_dir(6)?;
+	if obj.dir()? != 6 {
+		return Err(runtime!("test_appearance: dir != SOUTHEAST"));
+	}
+
+	// Nothing, NORTH|SOUTH and EAST|WEST aren't directions
+	for dir in [0, 3, 12, 16] {
+		match obj.set_dir(dir) {
+			Err(e) if e.kind == RuntimeKind::InvalidArgument => {}
+			_ => return Err(runtime!("test_appearance: set_dir({}) wasn't refused", dir)),
+		}
+	}
+
+	Ok(Value::from(true))
+}
@@ -0,0 +1,72 @@
+use auxtools::*;
+
+#[hook("/proc/auxtest_guarded_nested")]
+fn guarded_nested() {
+	let crashes = Proc::find("/proc/auxtest_guarded_crash")
+		.ok_or_else(|| runtime!("guarded_nested: couldn't find auxtest_guarded_crash"))?;
+
+	match crashes.call_guarded(&[]) {
+		Err(e) if e.message.contains("guarded crash") => Err(runtime!("outer crash")),
+		_ => Ok(Value::from_string("inner runtime wasn't caught")?),
+	}
+}
+
+#[hook("/proc/auxtest_call_guarded")]
+fn test_call_guarded() {
+	let crashes = Proc::find("/proc/auxtest_guarded_crash")
+		.ok_or_else(|| runtime!("test_call_guarded: couldn't find auxtest_guarded_crash"))?;
+
+	match crashes.call_guarded(&[]) {
+		Ok(_) => {
+			return Err(runtime!(
+				"test_call_guarded: a runtime didn't come back as an Err"
+			))
+		}
+		Err(e) if !e.message.contains("guarded crash") => {
+			return Err(runtime!(
+				"test_call_guarded: caught the wrong runtime: {}",
+				e.message
+			))
+		}
+		Err(_) => {}
+	}
+
+	// BYOND has to be left in a state where DM keeps working after the runtime
+	let after = Proc::find("/proc/auxtest_guarded_after")
+		.ok_or_else(|| runtime!("test_call_guarded: couldn't find auxtest_guarded_after"))?;
+	if after.call(&[&Value::from(10)])?.as_number()? != 10.0 {
+		return Err(runtime!(
+			"test_call_guarded: DM misbehaved after a guarded runtime"
+		));
+	}
+
+	let nested = Proc::find("/proc/auxtest_guarded_nested")
+		.ok_or_else(|| runtime!("test_call_guarded: couldn't find auxtest_guarded_nested"))?;
+	match nested.call_guarded(&[]) {
+		Err(e) if e.message.contains("outer crash") => {}
+		Err(e) => {
+			return Err(runtime!(
+				"test_call_guarded: nested guarded call caught the wrong runtime: {}",
+				e.message
+			))
+		}
+		Ok(v) => {
+			return Err(runtime!(
+				"test_call_guarded: nested guarded call didn't runtime: {:?}",
+				v
+			))
+		}
+	}
+
+	let concat = Proc::find("/proc/concat_strings")
+		.ok_or_else(|| runtime!("test_call_guarded: couldn't find concat_strings"))?;
+	let joined = concat.call_guarded(&[&Value::from_string("a")?, &Value::from_string("b")?])?;
+
+	if joined.as_string()? != "ab" {
+		return Err(runtime!(
+			"test_call_guarded: a call that didn't runtime returned the wrong value"
+		));
+	}
+
+	Ok(Value::from(true))
+}
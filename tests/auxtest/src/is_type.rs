@@ -0,0 +1,36 @@
+use auxtools::*;
+
+// is_type follows DM's istype(), parent_type included
+#[hook("/proc/auxtest_is_type")]
+fn test_is_type(child: Value, reparented: Value) {
+	if !child.is_type("/datum/auxtest_typed/child") {
+		return Err(runtime!("test_is_type: child isn't its own type"));
+	}
+
+	if !child.is_type("/datum/auxtest_typed") || !child.is_type("/datum") {
+		return Err(runtime!("test_is_type: child isn't its parent types"));
+	}
+
+	if !reparented.is_type("/datum/auxtest_typed") {
+		return Err(runtime!("test_is_type: parent_type wasn't followed"));
+	}
+
+	if reparented.is_type("/datum/auxtest_typed/child") {
+		return Err(runtime!("test_is_type: reparented is a sibling's type"));
+	}
+
+	if child.is_type("/datum/auxtest_not_a_type") {
+		return Err(runtime!("test_is_type: child is a type that doesn't exist"));
+	}
+
+	if Value::from(3).is_type("/datum") || Value::null().is_type("/datum") {
+		return Err(runtime!("test_is_type: a non-datum had a type"));
+	}
+
+	match child.get_type() {
+		Some(typepath) if typepath.to_string()? == "/datum/auxtest_typed/child" => {}
+		_ => return Err(runtime!("test_is_type: get_type was wrong")),
+	}
+
+	Ok(Value::from(true))
+}
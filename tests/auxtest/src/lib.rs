@@ -1,7 +1,17 @@
 use auxtools::*;
 
+mod appearance;
+mod guarded;
+mod is_type;
 mod lists;
+mod matrix;
+mod new_datum;
+mod panics;
+mod reentrancy;
+mod runtimes;
+mod spawn;
 mod strings;
+mod typed;
 mod value_from;
 mod weak;
 
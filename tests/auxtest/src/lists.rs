@@ -5,17 +5,17 @@ fn test_lists() {
 	let list_a = List::new();
 
 	// Should be empty
-	if list_a.len() != 0 {
+	if list_a.len()? != 0 {
 		return Err(runtime!("test_lists: list_a's len != 0"));
 	}
 
 	// Add 3 values
-	list_a.append(&Value::from(101));
-	list_a.append(&Value::from(102));
-	list_a.append(&Value::from(103));
+	list_a.append(&Value::from(101))?;
+	list_a.append(&Value::from(102))?;
+	list_a.append(&Value::from(103))?;
 
 	// Should contain 3 things
-	if list_a.len() != 3 {
+	if list_a.len()? != 3 {
 		return Err(runtime!("test_lists: list_a's len != 3"));
 	}
 
@@ -27,22 +27,60 @@ fn test_lists() {
 	}
 
 	// Should contain 4 things
-	if list_a.len() != 4 {
+	if list_a.len()? != 4 {
 		return Err(runtime!("test_lists: list_a's len != 4"));
 	}
 
 	// Remove list_a[2]
-	list_a.remove(&Value::from(102));
+	if list_a.remove(2)?.as_number()? != 102.0 {
+		return Err(runtime!("test_lists: list_a.remove(2) didn't return 102"));
+	}
 
 	// Now list_a[2] should be 103
 	if list_a.get(2)?.as_number()? != 103.0 {
 		return Err(runtime!("test_lists: list_a[2] != 103"));
 	}
 
+	if list_a.remove(0).is_ok() || list_a.remove(list_a.len()? + 1).is_ok() {
+		return Err(runtime!("test_lists: removed an out of bounds index"));
+	}
+
+	// Removing a key removes its value with it
+	list_a.remove(3)?;
+	if list_a.len()? != 2 || list_a.get(byond_string!("key"))?.is_truthy() {
+		return Err(runtime!("test_lists: list_a[\"key\"] survived removing it"));
+	}
+
+	// Only the element at the index goes, even when the same value is elsewhere in the list
+	let repeated: List = [5, 6, 7, 5].iter().map(|n| Value::from(*n)).collect();
+	repeated.remove(2)?;
+	repeated.push(8)?;
+	let remaining: Vec<Value> = repeated.iter().collect();
+	if remaining
+		!= vec![
+			Value::from(5),
+			Value::from(7),
+			Value::from(5),
+			Value::from(8),
+		] {
+		return Err(runtime!("test_lists: repeated didn't become [5, 7, 5, 8]"));
+	}
+
+	let assoc = List::new();
+	assoc.set_assoc(byond_string!("key"), 3)?;
+	if assoc.get_assoc(byond_string!("key"))?.as_number()? != 3.0 {
+		return Err(runtime!("test_lists: assoc[\"key\"] != 3"));
+	}
+	if assoc.get_assoc(1).is_ok() || assoc.set_assoc(1, 2).is_ok() {
+		return Err(runtime!(
+			"test_lists: a number was used as an associative key"
+		));
+	}
+
 	let list_b = List::with_size(6);
 
 	// This list should have 6 nulls in it
-	if list_b.len() != 6 {
+	if list_b.len()? != 6 {
 		return Err(runtime!("test_lists: list_b's len != 6"));
 	}
 
@@ -52,5 +90,62 @@ fn test_lists() {
 		}
 	}
 
+	// Iteration should visit every element in order
+	let list_c: List = (1..=3).map(Value::from).collect();
+	let collected: Vec<Value> = list_c.iter().collect();
+	if collected != vec![Value::from(1), Value::from(2), Value::from(3)] {
+		return Err(runtime!("test_lists: list_c didn't iterate as [1, 2, 3]"));
+	}
+
+	if !list_c.contains(&Value::from(2)) || list_c.contains(&Value::from(4)) {
+		return Err(runtime!(
+			"test_lists: list_c.contains gave the wrong answer"
+		));
+	}
+
+	let list_d = List::from_pairs(vec![
+		(byond_string!("a").into(), Value::from(1)),
+		(byond_string!("b").into(), Value::from(2)),
+	])?;
+	if list_d.len()? != 2 || list_d.get(byond_string!("b"))?.as_number()? != 2.0 {
+		return Err(runtime!("test_lists: list_d wasn't built from its pairs"));
+	}
+
+	// Generic indexing should agree with the list accessors
+	let list_d_value: Value = list_d.into();
+	list_d_value.index_set(byond_string!("c"), 3)?;
+	if list_d_value.index(byond_string!("c"))?.as_number()? != 3.0 {
+		return Err(runtime!(
+			"test_lists: list_d[\"c\"] != 3 through Value::index"
+		));
+	}
+
+	if Value::from(1).index(1).is_ok() {
+		return Err(runtime!("test_lists: a number could be indexed"));
+	}
+
+	let map = list_d_value.as_list()?.to_string_map()?;
+	if map.len() != 3 || map["a"].as_number()? != 1.0 || map["c"].as_number()? != 3.0 {
+		return Err(runtime!("test_lists: list_d didn't convert to a map"));
+	}
+
+	// Positional entries come out with null values
+	let mixed = List::new();
+	mixed.append(byond_string!("x"))?;
+	mixed.append(7)?;
+	mixed.set(byond_string!("y"), 2)?;
+	let map = mixed.to_hashmap()?;
+	if map.len() != 3
+		|| map[&Value::from(byond_string!("x"))] != Value::null()
+		|| map[&Value::from(7)] != Value::null()
+		|| map[&Value::from(byond_string!("y"))].as_number()? != 2.0
+	{
+		return Err(runtime!("test_lists: a mixed list didn't convert to a map"));
+	}
+
+	if List::new().iter().next().is_some() || !List::new().is_empty()? {
+		return Err(runtime!("test_lists: an empty list yielded elements"));
+	}
+
 	Ok(Value::from(true))
 }
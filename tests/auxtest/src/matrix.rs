@@ -0,0 +1,27 @@
+use auxtools::*;
+
+#[hook("/proc/auxtest_matrix")]
+fn test_matrix(m: Value) {
+	if m.as_matrix()? != Matrix::IDENTITY {
+		return Err(runtime!("test_matrix: matrix() isn't the identity matrix"));
+	}
+
+	let translated = Matrix {
+		c: 32.0,
+		f: -16.0,
+		..Matrix::IDENTITY
+	};
+	translated.write_to(&m)?;
+
+	if m.as_matrix()? != translated {
+		return Err(runtime!(
+			"test_matrix: written matrix didn't read back the same"
+		));
+	}
+
+	if Value::from(1.0).as_matrix().is_ok() {
+		return Err(runtime!("test_matrix: a number was read as a matrix"));
+	}
+
+	Ok(Value::from(true))
+}
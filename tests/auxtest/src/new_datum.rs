@@ -0,0 +1,28 @@
+use auxtools::*;
+
+#[hook("/proc/auxtest_new_datum")]
+fn test_new_datum() {
+	let datum = Value::new_datum(
+		"/datum/auxtest_created",
+		&[&Value::from(1), &Value::from(2)],
+	)?;
+
+	if !datum.is_exact_type("/datum/auxtest_created") {
+		return Err(runtime!("test_new_datum: created the wrong type"));
+	}
+
+	if datum.get_number(byond_string!("created_with"))? != 3.0 {
+		return Err(runtime!("test_new_datum: New() didn't get its arguments"));
+	}
+
+	match Value::new_datum("/datum/auxtest_not_a_type", &[]) {
+		Err(e) if e.kind == RuntimeKind::InvalidArgument => {}
+		_ => {
+			return Err(runtime!(
+				"test_new_datum: created a type that doesn't exist"
+			))
+		}
+	}
+
+	Ok(Value::from(true))
+}
@@ -0,0 +1,65 @@
+use auxtools::*;
+use std::cell::Cell;
+
+thread_local! {
+	static PANIC_ON: Cell<Option<raw_types::procs::ProcId>> = Cell::new(None);
+}
+
+fn panicking_call_hook(proc_id: raw_types::procs::ProcId, _: &Value, _: &Value, _: &[Value]) {
+	if PANIC_ON.with(|p| p.get()) == Some(proc_id) {
+		panic!("auxtest expected: call hook panic");
+	}
+}
+
+fn panicking_borrowed_hook(_: &Value, _: &Value, _: &[Value]) -> DMResult {
+	panic!("auxtest expected: borrowed hook panic");
+}
+
+// A panicking hook must neither stop a proc it didn't replace nor leak the args it borrowed
+#[hook("/proc/auxtest_hook_panics")]
+fn test_hook_panics() {
+	let target = Proc::find("/proc/auxtest_panic_target")
+		.ok_or_else(|| runtime!("test_hook_panics: couldn't find auxtest_panic_target"))?;
+
+	set_hook_error_policy(HookErrorPolicy::Ignore);
+
+	hook_all(panicking_call_hook);
+	PANIC_ON.with(|p| p.set(Some(target.id)));
+	let observed = target.call(&[]);
+	PANIC_ON.with(|p| p.set(None));
+
+	let arg = Value::from_string("auxtest panicking hook arg")?;
+	let before = arg.ref_count();
+	target
+		.hook_borrowed(panicking_borrowed_hook)
+		.map_err(|_| runtime!("test_hook_panics: couldn't hook auxtest_panic_target"))?;
+	let replaced = target.call(&[&arg]);
+	target
+		.unhook()
+		.map_err(|_| runtime!("test_hook_panics: couldn't unhook auxtest_panic_target"))?;
+	let after = arg.ref_count();
+
+	set_hook_error_policy(HookErrorPolicy::StackTrace);
+
+	if observed?.as_number()? != 7.0 {
+		return Err(runtime!(
+			"test_hook_panics: a panicking call hook stopped the proc from running"
+		));
+	}
+
+	if replaced? != Value::null() {
+		return Err(runtime!(
+			"test_hook_panics: a panicking hook didn't make its proc return null"
+		));
+	}
+
+	if before != after {
+		return Err(runtime!(
+			"test_hook_panics: a panicking borrowed hook leaked its args ({:?} refs before, {:?} after)",
+			before,
+			after
+		));
+	}
+
+	Ok(Value::from(true))
+}
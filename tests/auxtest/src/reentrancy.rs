@@ -0,0 +1,42 @@
+use auxtools::*;
+
+#[hook("/proc/auxtest_reentrant_inner")]
+fn reentrant_inner() {
+	Ok(Value::from(5))
+}
+
+fn temporary_hook(_src: &Value, _usr: &Value, _args: Vec<Value>) -> DMResult {
+	Ok(Value::null())
+}
+
+// Hooks that call hooked procs, or hook procs themselves, mustn't find the hook table borrowed
+#[hook("/proc/auxtest_reentrancy")]
+fn test_reentrancy() {
+	let inner = Proc::find("/proc/auxtest_reentrant_inner")
+		.ok_or_else(|| runtime!("test_reentrancy: couldn't find auxtest_reentrant_inner"))?;
+
+	if inner.call(&[])?.as_number()? != 5.0 {
+		return Err(runtime!(
+			"test_reentrancy: a hooked proc called from a hook wasn't hooked"
+		));
+	}
+
+	let unhooked = Proc::find("/proc/auxtest_reentrant_unhooked")
+		.ok_or_else(|| runtime!("test_reentrancy: couldn't find auxtest_reentrant_unhooked"))?;
+
+	unhooked
+		.hook(temporary_hook)
+		.map_err(|_| runtime!("test_reentrancy: couldn't hook a proc from inside a hook"))?;
+	let hooked_result = unhooked.call(&[]);
+	unhooked
+		.unhook()
+		.map_err(|_| runtime!("test_reentrancy: couldn't unhook a proc from inside a hook"))?;
+
+	if hooked_result? != Value::null() {
+		return Err(runtime!(
+			"test_reentrancy: a proc hooked from inside a hook didn't run its hook"
+		));
+	}
+
+	Ok(Value::from(true))
+}
@@ -0,0 +1,47 @@
+use auxtools::*;
+use std::cell::RefCell;
+
+thread_local! {
+	static LAST_RUNTIME: RefCell<Option<String>> = RefCell::new(None);
+}
+
+#[runtime_filter]
+fn rewrite_marked(message: &str) -> RuntimeAction {
+	if message.contains("auxtest rewrite me") {
+		RuntimeAction::Rewrite("auxtest expected: rewritten".to_owned())
+	} else {
+		RuntimeAction::Display
+	}
+}
+
+#[runtime_handler]
+fn record_runtime(message: &str) {
+	LAST_RUNTIME.with(|last| *last.borrow_mut() = Some(message.to_owned()));
+}
+
+// A rewritten runtime must still stop the proc that caused it
+#[hook("/proc/auxtest_runtime_filter")]
+fn test_runtime_filter() {
+	let crashes = Proc::find("/proc/auxtest_filtered_crash")
+		.ok_or_else(|| runtime!("test_runtime_filter: couldn't find auxtest_filtered_crash"))?;
+	let _ = crashes.call(&[]);
+
+	let last = LAST_RUNTIME.with(|last| last.borrow_mut().take());
+	if last.as_deref() != Some("auxtest expected: rewritten") {
+		return Err(runtime!(
+			"test_runtime_filter: handlers saw {:?} instead of the rewritten runtime",
+			last
+		));
+	}
+
+	if Value::globals()
+		.get(byond_string!("auxtest_after_filtered_crash"))?
+		.is_truthy()
+	{
+		return Err(runtime!(
+			"test_runtime_filter: code after a rewritten runtime still ran"
+		));
+	}
+
+	Ok(Value::from(true))
+}
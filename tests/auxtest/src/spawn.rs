@@ -0,0 +1,18 @@
+use auxtools::*;
+
+// Spawned procs mustn't run inside the code that spawned them. do_tests checks they run afterwards
+#[hook("/proc/auxtest_spawn")]
+fn test_spawn() {
+	let spawned = Proc::find("/proc/auxtest_spawned")
+		.ok_or_else(|| runtime!("test_spawn: couldn't find auxtest_spawned"))?;
+	spawned.spawn(&[&Value::from(3)])?;
+
+	if Value::globals()
+		.get(byond_string!("auxtest_spawned_with"))?
+		.is_truthy()
+	{
+		return Err(runtime!("test_spawn: the spawned proc ran straight away"));
+	}
+
+	Ok(Value::from(true))
+}
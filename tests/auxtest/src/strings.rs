@@ -114,6 +114,14 @@ fn test_strings() {
 			));
 		}
 
+		// Goes through /proc/auxtools_equals, which uses DM's own ==
+		let text = Value::from_string("auxtest")?;
+		if !text.equals_dm(&Value::from_string("auxtest")?)?
+			|| Value::from(1).equals_dm(&Value::from(2))?
+		{
+			return Err(runtime!("test_string: equals_dm disagreed with DM's =="));
+		}
+
 		Ok(Value::from(true))
 	}
 }
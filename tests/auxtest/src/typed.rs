@@ -0,0 +1,29 @@
+use auxtools::*;
+
+fn hooked_speak(_: &Value, _: &Value, _: Vec<Value>) -> DMResult {
+	Value::from_string("hooked")
+}
+
+// Hooks on a type's proc catch calls on subtypes that don't override it
+#[hook("/proc/auxtest_typed_hooks")]
+fn test_typed_hooks(child: Value) {
+	if hook("/datum/auxtest_typed/child/proc/speak", hooked_speak).is_ok() {
+		return Err(runtime!(
+			"test_typed_hooks: hooked a proc the child type doesn't define"
+		));
+	}
+
+	hook("/datum/auxtest_typed/proc/speak", hooked_speak)
+		.map_err(|e| runtime!("test_typed_hooks: couldn't hook speak: {}", e))?;
+	let spoken = child.call("speak", &[]);
+	unhook("/datum/auxtest_typed/proc/speak")
+		.map_err(|e| runtime!("test_typed_hooks: couldn't unhook speak: {}", e))?;
+
+	if spoken?.as_string()? != "hooked" {
+		return Err(runtime!(
+			"test_typed_hooks: a hook on the parent type didn't catch a call on the child"
+		));
+	}
+
+	Ok(Value::from(true))
+}
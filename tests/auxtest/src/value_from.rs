@@ -16,7 +16,7 @@ fn test_value_from() {
 	vector.push(5.into());
 	let value = Value::from(&vector);
 	let list = List::from_value(&value)?;
-	if list.len() != 1 {
+	if list.len()? != 1 {
 		return Err(runtime!(
 			"value_from: Vec with one entry did not result in a list length of one"
 		));
@@ -60,7 +60,7 @@ fn assert_meow_equals_one(value: Value) -> Result<(), Runtime> {
 
 	let list = List::from_value(&value)?;
 
-	if list.len() != 1 {
+	if list.len()? != 1 {
 		return Err(runtime!(
 			"value_from: Hashmap with one key did not result in a list length of one"
 		));